@@ -515,16 +515,29 @@ parameter_types! {
 	// phase durations. 1/4 of the last session for each.
 	pub const SignedPhase: u32 = EPOCH_DURATION_IN_BLOCKS / 4;
 	pub const UnsignedPhase: u32 = EPOCH_DURATION_IN_BLOCKS / 4;
+	// elections are an era apart, no need for a cooldown after `elect`.
+	pub const PostElectionCooldown: BlockNumber = 0;
 
 	// fallback: no need to do on-chain phragmen initially.
 	pub const Fallback: pallet_election_provider_multi_phase::FallbackStrategy =
 		pallet_election_provider_multi_phase::FallbackStrategy::Nothing;
 
+	// an election happens every era (one hour): keep the history of the last day.
+	pub const MultiPhaseHistoryDepth: u32 = 24;
+
 	pub SolutionImprovementThreshold: Perbill = Perbill::from_rational_approximation(1u32, 10_000);
+	pub BetterUnsignedThreshold: Perbill = Perbill::from_rational_approximation(5u32, 10_000);
+
+	// snapshot voters are stored in pages of this size.
+	pub const VoterSnapshotPerPage: u32 = 1024;
 
 	// miner configs
 	pub const MultiPhaseUnsignedPriority: TransactionPriority = StakingUnsignedPriority::get() - 1u64;
+	pub const MultiPhasePriorityStrategy: pallet_election_provider_multi_phase::PriorityStrategy =
+		pallet_election_provider_multi_phase::PriorityStrategy::ScoreWeighted;
 	pub const MinerMaxIterations: u32 = 10;
+	pub const MinerSolver: pallet_election_provider_multi_phase::SolverStrategy =
+		pallet_election_provider_multi_phase::SolverStrategy::SeqPhragmen;
 	pub MinerMaxWeight: Weight = RuntimeBlockWeights::get()
 		.get(DispatchClass::Normal)
 		.max_extrinsic.expect("Normal extrinsics have a weight limit configured; qed")
@@ -536,14 +549,26 @@ impl pallet_election_provider_multi_phase::Config for Runtime {
 	type Currency = Balances;
 	type SignedPhase = SignedPhase;
 	type UnsignedPhase = UnsignedPhase;
+	type PostElectionCooldown = PostElectionCooldown;
 	type SolutionImprovementThreshold = MinSolutionScoreBump;
+	type BetterUnsignedThreshold = BetterUnsignedThreshold;
+	type MaxSupportPerTarget = ();
+	type VoterSnapshotPerPage = VoterSnapshotPerPage;
 	type MinerMaxIterations = MinerMaxIterations;
+	type MinerSolver = MinerSolver;
 	type MinerMaxWeight = MinerMaxWeight;
 	type MinerTxPriority = MultiPhaseUnsignedPriority;
+	type PriorityStrategy = MultiPhasePriorityStrategy;
 	type DataProvider = Staking;
 	type OnChainAccuracy = Perbill;
 	type CompactSolution = pallet_staking::CompactAssignments;
 	type Fallback = Fallback;
+	type HistoryDepth = MultiPhaseHistoryDepth;
+	type ForceOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, CouncilCollective>,
+	>;
 	type WeightInfo = pallet_election_provider_multi_phase::weights::SubstrateWeight<Runtime>;
 	type BenchmarkingConfig = ();
 }
@@ -1374,6 +1399,84 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_election_provider_multi_phase_rpc_runtime_api::TwoPhaseApi<
+		Block,
+		AccountId,
+		BlockNumber,
+		pallet_staking::CompactAssignments,
+	> for Runtime {
+		fn round_snapshot()
+			-> Option<pallet_election_provider_multi_phase::RoundSnapshot<AccountId>>
+		{
+			ElectionProviderMultiPhase::snapshot()
+		}
+
+		fn current_phase() -> pallet_election_provider_multi_phase::Phase<BlockNumber> {
+			ElectionProviderMultiPhase::current_phase()
+		}
+
+		fn round() -> u32 {
+			ElectionProviderMultiPhase::round()
+		}
+
+		fn export_round_state()
+			-> pallet_election_provider_multi_phase::RoundStateExport<AccountId, BlockNumber>
+		{
+			ElectionProviderMultiPhase::export_round_state()
+		}
+
+		fn check_solution(
+			solution: pallet_election_provider_multi_phase::RawSolution<
+				pallet_staking::CompactAssignments,
+			>,
+		) -> Result<
+			pallet_election_provider_multi_phase_rpc_runtime_api::ElectionScore,
+			pallet_election_provider_multi_phase::FeasibilityError,
+		> {
+			ElectionProviderMultiPhase::check_solution(solution)
+		}
+
+		fn queued_support_of(
+			target: AccountId,
+		) -> Option<(
+			pallet_election_provider_multi_phase_rpc_runtime_api::ExtendedBalance,
+			Vec<(AccountId, pallet_election_provider_multi_phase_rpc_runtime_api::ExtendedBalance)>,
+		)> {
+			ElectionProviderMultiPhase::queued_support_of(&target)
+		}
+
+		fn blocks_until_next_election() -> BlockNumber {
+			ElectionProviderMultiPhase::blocks_until_next_election()
+		}
+
+		fn last_election_compute()
+			-> Option<pallet_election_provider_multi_phase::ElectionCompute>
+		{
+			ElectionProviderMultiPhase::last_election_compute()
+		}
+
+		fn score_improvement_over_queued(
+			score: pallet_election_provider_multi_phase_rpc_runtime_api::ElectionScore,
+		) -> Option<Perbill> {
+			ElectionProviderMultiPhase::score_improvement_over_queued(score)
+		}
+
+		fn snapshot_voters_paged(
+			page: u32,
+			page_size: u32,
+		) -> Vec<(
+			AccountId,
+			pallet_election_provider_multi_phase_rpc_runtime_api::VoteWeight,
+			Vec<AccountId>,
+		)> {
+			ElectionProviderMultiPhase::snapshot_voters_paged(page, page_size)
+		}
+
+		fn snapshot_targets_paged(page: u32, page_size: u32) -> Vec<AccountId> {
+			ElectionProviderMultiPhase::snapshot_targets_paged(page, page_size)
+		}
+	}
+
 	impl pallet_mmr::primitives::MmrApi<
 		Block,
 		mmr::Hash,
@@ -0,0 +1,67 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for the election provider multi phase module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+use sp_runtime::Perbill;
+
+pub use pallet_election_provider_multi_phase::{
+	ElectionCompute, FeasibilityError, Phase, RawSolution, RoundSnapshot, RoundStateExport,
+};
+pub use sp_npos_elections::{ElectionScore, ExtendedBalance, VoteWeight};
+
+sp_api::decl_runtime_apis! {
+	/// The API of the election provider multi phase pallet, mainly for off-chain miners.
+	pub trait TwoPhaseApi<AccountId, BlockNumber, Compact> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+		Compact: Codec,
+	{
+		/// The snapshot of the current round, if any.
+		fn round_snapshot() -> Option<RoundSnapshot<AccountId>>;
+		/// The current phase.
+		fn current_phase() -> Phase<BlockNumber>;
+		/// The current round.
+		fn round() -> u32;
+		/// The entire state of the current round, read at once.
+		fn export_round_state() -> RoundStateExport<AccountId, BlockNumber>;
+		/// Dry-run the feasibility check of `solution` against the current snapshot, without
+		/// queueing it.
+		fn check_solution(solution: RawSolution<Compact>) -> Result<ElectionScore, FeasibilityError>;
+		/// The total and the backers of `target` in the queued solution, if it is a winner.
+		fn queued_support_of(
+			target: AccountId,
+		) -> Option<(ExtendedBalance, Vec<(AccountId, ExtendedBalance)>)>;
+		/// The number of blocks until the next election, as predicted by the data provider.
+		fn blocks_until_next_election() -> BlockNumber;
+		/// How the last election was computed, if any.
+		fn last_election_compute() -> Option<ElectionCompute>;
+		/// The improvement of the first element of `score` over that of the queued solution, if any.
+		fn score_improvement_over_queued(score: ElectionScore) -> Option<Perbill>;
+		/// The voters of the snapshot at `page`, when read in pages of `page_size` voters.
+		fn snapshot_voters_paged(
+			page: u32,
+			page_size: u32,
+		) -> Vec<(AccountId, VoteWeight, Vec<AccountId>)>;
+		/// The targets of the snapshot at `page`, when read in pages of `page_size` targets.
+		fn snapshot_targets_paged(page: u32, page_size: u32) -> Vec<AccountId>;
+	}
+}
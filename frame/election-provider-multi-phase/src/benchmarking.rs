@@ -102,12 +102,11 @@ fn solution_with_size<T: Config>(
 	assert_eq!(all_voters.len() as u32, size.voters);
 	assert_eq!(winners.len() as u32, desired_targets);
 
-	<SnapshotMetadata<T>>::put(SolutionOrSnapshotSize {
-		voters: all_voters.len() as u32,
-		targets: targets.len() as u32,
+	<MultiPhase<T>>::put_snapshot(RoundSnapshot {
+		voters: all_voters.clone(),
+		targets: targets.clone(),
+		desired_targets,
 	});
-	<DesiredTargets<T>>::put(desired_targets);
-	<Snapshot<T>>::put(RoundSnapshot { voters: all_voters.clone(), targets: targets.clone() });
 
 	// write the snapshot to staking or whoever is the data provider.
 	T::DataProvider::put_snapshot(all_voters.clone(), targets.clone());
@@ -141,6 +140,40 @@ fn solution_with_size<T: Config>(
 	RawSolution { compact, score, round }
 }
 
+/// The encoded pages of the voters of the snapshot, as split by [`Config::VoterSnapshotPerPage`].
+///
+/// In the worst case, every one of them is read by [`Pallet::feasibility_check`].
+fn encoded_voter_pages<T: Config>() -> Vec<Vec<u8>> {
+	(0..<MultiPhase<T>>::snapshot_pages().unwrap_or_default())
+		.map(|page| <SnapshotVoters<T>>::get(page).unwrap().encode())
+		.collect()
+}
+
+/// Stage a snapshot of `v` voters, paged by [`Config::VoterSnapshotPerPage`], and `t` targets,
+/// along with a valid solution with `a` active voters and `d` winners, and run
+/// [`Pallet::feasibility_check`] on it.
+///
+/// Returns the time taken by the check alone, as weight. This is the same measurement that the
+/// `feasibility_check` benchmark makes for these components, i.e. the computational part of
+/// [`WeightInfo::feasibility_check`].
+#[cfg(feature = "runtime-benchmarks")]
+pub fn benchmark_feasibility_check<T: Config>(
+	v: u32,
+	t: u32,
+	a: u32,
+	d: u32,
+) -> Result<Weight, FeasibilityError> {
+	let size = SolutionOrSnapshotSize { voters: v, targets: t };
+	let raw_solution = solution_with_size::<T>(size, a, d);
+
+	let start = frame_benchmarking::benchmarking::current_time();
+	<MultiPhase<T>>::feasibility_check(raw_solution, ElectionCompute::Unsigned)?;
+	let elapsed = frame_benchmarking::benchmarking::current_time().saturating_sub(start);
+
+	// measured in nanoseconds, while a unit of weight is a picosecond.
+	Ok((elapsed as Weight).saturating_mul(1_000))
+}
+
 benchmarks! {
 	on_initialize_nothing {
 		assert!(<MultiPhase<T>>::current_phase().is_off());
@@ -211,12 +244,14 @@ benchmarks! {
 		assert!(<MultiPhase<T>>::queued_solution().is_none());
 		<CurrentPhase<T>>::put(Phase::Unsigned((true, 1u32.into())));
 
-		// encode the most significant storage item that needs to be decoded in the dispatch.
-		let encoded_snapshot = <MultiPhase<T>>::snapshot().unwrap().encode();
+		// encode the most significant storage items that need to be decoded in the dispatch.
+		let encoded_pages = encoded_voter_pages::<T>();
 		let encoded_call = <Call<T>>::submit_unsigned(raw_solution.clone(), witness).encode();
 	}: {
 		assert_ok!(<MultiPhase<T>>::submit_unsigned(RawOrigin::None.into(), raw_solution, witness));
-		let _decoded_snap = <RoundSnapshot<T::AccountId> as Decode>::decode(&mut &*encoded_snapshot).unwrap();
+		for encoded_page in encoded_pages.iter() {
+			let _decoded_page = <VoterPage<T> as Decode>::decode(&mut &**encoded_page).unwrap();
+		}
 		let _decoded_call = <Call<T> as Decode>::decode(&mut &*encoded_call).unwrap();
 	} verify {
 		assert!(<MultiPhase<T>>::queued_solution().is_some());
@@ -240,11 +275,13 @@ benchmarks! {
 		assert_eq!(raw_solution.compact.voter_count() as u32, a);
 		assert_eq!(raw_solution.compact.unique_targets().len() as u32, d);
 
-		// encode the most significant storage item that needs to be decoded in the dispatch.
-		let encoded_snapshot = <MultiPhase<T>>::snapshot().unwrap().encode();
+		// encode the most significant storage items that need to be decoded in the dispatch.
+		let encoded_pages = encoded_voter_pages::<T>();
 	}: {
 		assert_ok!(<MultiPhase<T>>::feasibility_check(raw_solution, ElectionCompute::Unsigned));
-		let _decoded_snap = <RoundSnapshot<T::AccountId> as Decode>::decode(&mut &*encoded_snapshot).unwrap();
+		for encoded_page in encoded_pages.iter() {
+			let _decoded_page = <VoterPage<T> as Decode>::decode(&mut &**encoded_page).unwrap();
+		}
 	}
 }
 
@@ -255,11 +292,12 @@ mod test {
 
 	#[test]
 	fn test_benchmarks() {
-		ExtBuilder::default().build_and_execute(|| {
+		// the voters of the snapshot span several pages.
+		ExtBuilder::default().voter_snapshot_per_page(500).build_and_execute(|| {
 			assert_ok!(test_benchmark_feasibility_check::<Runtime>());
 		});
 
-		ExtBuilder::default().build_and_execute(|| {
+		ExtBuilder::default().voter_snapshot_per_page(500).build_and_execute(|| {
 			assert_ok!(test_benchmark_submit_unsigned::<Runtime>());
 		});
 
@@ -279,4 +317,18 @@ mod test {
 			assert_ok!(test_benchmark_create_snapshot::<Runtime>());
 		});
 	}
+
+	#[test]
+	#[cfg(feature = "runtime-benchmarks")]
+	fn test_benchmark_feasibility_check_of_1000_voters() {
+		ExtBuilder::default().voter_snapshot_per_page(100).build_and_execute(|| {
+			let weight = benchmark_feasibility_check::<Runtime>(1000, 100, 500, 30).unwrap();
+			assert!(weight > 0);
+
+			// the check ran against a paged snapshot of the requested size.
+			assert_eq!(crate::Pallet::<Runtime>::snapshot_pages(), Some(10));
+			assert_eq!(crate::Pallet::<Runtime>::snapshot_metadata().unwrap().voters, 1000);
+			assert_eq!(crate::Pallet::<Runtime>::snapshot().unwrap().targets.len(), 100);
+		});
+	}
 }
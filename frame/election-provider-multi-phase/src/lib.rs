@@ -100,13 +100,19 @@
 //! valid if propagated, and it acts similar to an inherent.
 //!
 //! Validators will only submit solutions if the one that they have computed is sufficiently better
-//! than the best queued one (see [`pallet::Config::SolutionImprovementThreshold`]) and will limit
+//! than the best queued one (see [`pallet::Config::BetterUnsignedThreshold`]) and will limit
 //! the weigh of the solution to [`pallet::Config::MinerMaxWeight`].
 //!
 //! The unsigned phase can be made passive depending on how the previous signed phase went, by
 //! setting the first inner value of [`Phase`] to `false`. For now, the signed phase is always
 //! active.
 //!
+//! The unsigned transaction of a round provides a tag unique to that round, and stays in the pool
+//! for the duration of the unsigned phase at most. If it is not included in time, it may linger
+//! in the pool past the end of its round. It then no longer passes validation (its round is
+//! stale), so the pool drops it upon the next re-validation. It never stands in the way of the
+//! solution of the next round, which provides a different tag and is submitted as usual.
+//!
 //! ### Fallback
 //!
 //! If we reach the end of both phases (i.e. call to [`ElectionProvider::elect`] happens) and no
@@ -115,6 +121,14 @@
 //! reduction post-processing. See [`onchain::OnChainSequentialPhragmen`]. The
 //! [`FallbackStrategy::Nothing`] should probably only be used for testing, and returns an error.
 //!
+//! ### Emergency Phase
+//!
+//! If the fallback fails as well, `elect` returns an error and the pallet enters
+//! [`Phase::Emergency`]. No further phases are started, and the snapshot is kept around, until
+//! [`pallet::Config::ForceOrigin`] sets a result via `set_emergency_solution`. This result is then
+//! returned by the next call to `elect`. Alternatively, `clear_round_data` abandons the round and
+//! goes back to [`Phase::Off`].
+//!
 //! ## Feasible Solution (correct solution)
 //!
 //! All submissions must undergo a feasibility check. Signed solutions are checked on by one at the
@@ -122,8 +136,10 @@
 //! is as follows:
 //!
 //! 0. **all** of the used indices must be correct.
-//! 1. present *exactly* correct number of winners.
-//! 2. any assignment is checked to match with [`RoundSnapshot::voters`].
+//! 1. present *exactly* correct number of winners, i.e. [`RoundSnapshot::desired_targets`], or all
+//!    of the targets if there are fewer of them.
+//! 2. any assignment is checked to match with [`RoundSnapshot::voters`], and each voter can only
+//!    appear once.
 //! 3. the claimed score is valid, based on the fixed point arithmetic accuracy.
 //!
 //! ## Accuracy
@@ -179,8 +195,9 @@
 //! spare some work in the validators
 //!
 //! **Allow smaller solutions and build up**: For now we only allow solutions that are exactly
-//! [`DesiredTargets`], no more, no less. Over time, we can change this to a [min, max] where any
-//! solution within this range is acceptable, where bigger solutions are prioritized.
+//! [`RoundSnapshot::desired_targets`], no more, no less. Over time, we can change this to a
+//! [min, max] where any solution within this range is acceptable, where bigger solutions are
+//! prioritized.
 //!
 //! **Recursive Fallback**: Currently, the fallback is a separate enum. A different and fancier way
 //! of doing this would be to have the fallback be another
@@ -205,25 +222,31 @@
 
 use codec::{Decode, Encode};
 use frame_support::{
-	dispatch::DispatchResultWithPostInfo,
-	ensure,
+	dispatch::{DispatchResult, DispatchResultWithPostInfo},
+	ensure, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
 	traits::{Currency, Get, ReservableCurrency},
-	weights::Weight,
+	weights::{DispatchClass, Weight},
 };
 use frame_system::{ensure_none, offchain::SendTransactionTypes};
-use sp_election_providers::{ElectionDataProvider, ElectionProvider, onchain};
+use sp_election_providers::{BoundsExceeded, ElectionDataProvider, ElectionProvider, onchain};
 use sp_npos_elections::{
-	assignment_ratio_to_staked_normalized, is_score_better, CompactSolution, ElectionScore,
-	EvaluateSupport, PerThing128, Supports, VoteWeight,
+	assignment_ratio_to_staked_normalized, is_score_better, Assignment, CompactSolution,
+	DisplayScore, ElectionScore, EvaluateSupport, ExtendedBalance, PerThing128, Supports,
+	VoteWeight,
 };
 use sp_runtime::{
 	transaction_validity::{
 		InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
 		TransactionValidityError, ValidTransaction,
 	},
+	traits::{Hash, Saturating},
 	DispatchError, PerThing, Perbill, RuntimeDebug, SaturatedConversion,
 };
-use sp_std::prelude::*;
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	convert::TryInto,
+	prelude::*,
+};
 use sp_arithmetic::{
 	UpperOf,
 	traits::{Zero, CheckedAdd},
@@ -231,10 +254,13 @@ use sp_arithmetic::{
 
 #[cfg(any(feature = "runtime-benchmarks", test))]
 mod benchmarking;
+#[cfg(feature = "runtime-benchmarks")]
+pub use benchmarking::benchmark_feasibility_check;
 #[cfg(test)]
 mod mock;
 #[macro_use]
 pub mod helpers;
+mod migrations;
 
 const LOG_TARGET: &'static str = "runtime::election-provider";
 
@@ -294,6 +320,9 @@ pub enum Phase<Bn> {
 	/// Unsigned phase. First element is whether it is open or not, second the starting block
 	/// number.
 	Unsigned((bool, Bn)),
+	/// The call to `elect` failed, and no fallback was able to provide a result. Only a solution
+	/// set by [`Config::ForceOrigin`] via `set_emergency_solution` can end this phase.
+	Emergency,
 }
 
 impl<Bn> Default for Phase<Bn> {
@@ -327,6 +356,11 @@ impl<Bn: PartialEq + Eq> Phase<Bn> {
 	pub fn is_off(&self) -> bool {
 		matches!(self, Phase::Off)
 	}
+
+	/// Weather the phase is emergency or not.
+	pub fn is_emergency(&self) -> bool {
+		matches!(self, Phase::Emergency)
+	}
 }
 
 /// A configuration for the pallet to indicate what should happen in the case of a fallback i.e.
@@ -342,6 +376,33 @@ pub enum FallbackStrategy {
 	Nothing,
 }
 
+/// A configuration for the pallet to indicate which algorithm the unsigned miner uses.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum SolverStrategy {
+	/// Sequential phragmen, see [`sp_npos_elections::seq_phragmen`].
+	SeqPhragmen,
+	/// PhragMMS, see [`sp_npos_elections::phragmms`].
+	///
+	/// Usually yields a better minimal backing than [`SolverStrategy::SeqPhragmen`], at a similar
+	/// cost.
+	PhragMMS,
+}
+
+/// A configuration for the pallet to indicate how unsigned solutions are prioritized in the
+/// transaction pool.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum PriorityStrategy {
+	/// Always use the given priority, regardless of the score.
+	Fixed(TransactionPriority),
+	/// Use [`pallet::Config::MinerTxPriority`], increased by the first element of the score.
+	///
+	/// This orders competing solutions by their minimal backing.
+	ScoreWeighted,
+	/// Always use the maximum priority, such that unsigned solutions outrank any other
+	/// transaction.
+	AlwaysTop,
+}
+
 /// The type of `Computation` that provided this election data.
 #[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug)]
 pub enum ElectionCompute {
@@ -351,6 +412,10 @@ pub enum ElectionCompute {
 	Signed,
 	/// Election was computed with an unsigned submission.
 	Unsigned,
+	/// Election was set by [`Config::ForceOrigin`] during [`Phase::Emergency`].
+	Emergency,
+	/// Election was computed with a solution queued by [`Config::ForceOrigin`].
+	Governance,
 }
 
 impl Default for ElectionCompute {
@@ -382,6 +447,15 @@ impl<C: Default> Default for RawSolution<C> {
 	}
 }
 
+/// The blocks at which the phases of a round started.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
+pub struct PhaseStarts<Bn> {
+	/// The block at which the signed phase started, if any.
+	pub signed: Option<Bn>,
+	/// The block at which the unsigned phase started, if any.
+	pub unsigned: Option<Bn>,
+}
+
 /// A checked solution, ready to be enacted.
 #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
 pub struct ReadySolution<A> {
@@ -396,6 +470,8 @@ pub struct ReadySolution<A> {
 	score: ElectionScore,
 	/// How this election was computed.
 	compute: ElectionCompute,
+	/// The round for which this solution was built.
+	round: u32,
 }
 
 /// A snapshot of all the data that is needed for en entire round. They are provided by
@@ -404,10 +480,127 @@ pub struct ReadySolution<A> {
 /// These are stored together because they are often accessed together.
 #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, Default)]
 pub struct RoundSnapshot<A> {
-	/// All of the voters.
+	/// All of the voters, with their vote weight and their votes.
+	///
+	/// Note that the [`VoteWeight`] is not a balance: it is the stake of the voter as converted
+	/// by the data provider (e.g. via `CurrencyToVote` in staking), and must be converted back
+	/// before being compared to any balance.
 	pub voters: Vec<(A, VoteWeight, Vec<A>)>,
 	/// All of the targets.
 	pub targets: Vec<A>,
+	/// The number of targets to elect, as of the time this snapshot was taken.
+	///
+	/// This is the only source of the desired targets of a round, see
+	/// [`Pallet::desired_targets`].
+	pub desired_targets: u32,
+}
+
+impl<A> RoundSnapshot<A> {
+	/// The sum of the vote weight of all voters.
+	///
+	/// This is an [`ExtendedBalance`], since the sum may not fit in a [`VoteWeight`].
+	pub fn total_vote_weight(&self) -> ExtendedBalance {
+		self.voters
+			.iter()
+			.fold(Zero::zero(), |acc: ExtendedBalance, (_, weight, _)| {
+				acc.saturating_add(*weight as ExtendedBalance)
+			})
+	}
+}
+
+/// Decode a vector of at most `bound` items, each decoded by `item`.
+///
+/// A longer length prefix fails early, such that a corrupt length prefix in storage cannot lead to
+/// an unbounded allocation.
+fn decode_bounded_vec<I: codec::Input, X>(
+	input: &mut I,
+	bound: u32,
+	error: &'static str,
+	mut item: impl FnMut(&mut I) -> Result<X, codec::Error>,
+) -> Result<Vec<X>, codec::Error> {
+	let len = <codec::Compact<u32>>::decode(input)?.0;
+	if len > bound {
+		return Err(error.into());
+	}
+
+	// NOTE: no capacity is reserved, `len` might still be bogus.
+	let mut items = Vec::new();
+	for _ in 0..len {
+		items.push(item(input)?);
+	}
+	Ok(items)
+}
+
+/// A page of the voters of the snapshot, see [`SnapshotVoters`].
+///
+/// Decoding a page that claims to contain more than [`Config::VoterSnapshotPerPage`] voters, or a
+/// voter with more votes than a [`Config::CompactSolution`] can hold, fails early.
+#[derive(CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound)]
+pub struct VoterPage<T: Config>(pub Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)>);
+
+impl<T: Config> Encode for VoterPage<T> {
+	fn size_hint(&self) -> usize {
+		self.0.size_hint()
+	}
+
+	fn encode_to<O: codec::Output + ?Sized>(&self, dest: &mut O) {
+		self.0.encode_to(dest)
+	}
+}
+
+impl<T: Config> codec::EncodeLike for VoterPage<T> {}
+
+impl<T: Config> Decode for VoterPage<T> {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let max_votes = <Pallet<T>>::max_votes_per_voter();
+		decode_bounded_vec(
+			input,
+			T::VoterSnapshotPerPage::get(),
+			"voter page exceeds `VoterSnapshotPerPage`",
+			|input| {
+				let who = Decode::decode(input)?;
+				let stake = Decode::decode(input)?;
+				let votes = decode_bounded_vec(
+					input,
+					max_votes,
+					"voter exceeds the votes of `CompactSolution`",
+					|input| Decode::decode(input),
+				)?;
+				Ok((who, stake, votes))
+			},
+		)
+		.map(Self)
+	}
+}
+
+/// The targets of the snapshot, see [`SnapshotTargets`].
+///
+/// Decoding more targets than a [`Config::CompactSolution`] can index fails early.
+#[derive(CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound)]
+pub struct TargetList<T: Config>(pub Vec<T::AccountId>);
+
+impl<T: Config> Encode for TargetList<T> {
+	fn size_hint(&self) -> usize {
+		self.0.size_hint()
+	}
+
+	fn encode_to<O: codec::Output + ?Sized>(&self, dest: &mut O) {
+		self.0.encode_to(dest)
+	}
+}
+
+impl<T: Config> codec::EncodeLike for TargetList<T> {}
+
+impl<T: Config> Decode for TargetList<T> {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		decode_bounded_vec(
+			input,
+			<Pallet<T>>::max_snapshot_targets(),
+			"targets exceed the index of `CompactSolution`",
+			|input| Decode::decode(input),
+		)
+		.map(Self)
+	}
 }
 
 /// Encodes the length of a solution or a snapshot.
@@ -425,6 +618,25 @@ pub struct SolutionOrSnapshotSize {
 	targets: u32,
 }
 
+/// The entire state of an ongoing round, as exported by [`Pallet::export_round_state`].
+///
+/// This is meant for off-chain analysis and backups, and is never stored on-chain.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct RoundStateExport<A, Bn> {
+	/// The current round, see [`Round`].
+	pub round: u32,
+	/// The current phase, see [`CurrentPhase`].
+	pub phase: Phase<Bn>,
+	/// The snapshot of the round, if any, see [`Pallet::snapshot`].
+	pub snapshot: Option<RoundSnapshot<A>>,
+	/// The snapshot metadata, if any, see [`SnapshotMetadata`].
+	pub snapshot_metadata: Option<SolutionOrSnapshotSize>,
+	/// The desired targets, if any, see [`Pallet::desired_targets`].
+	pub desired_targets: Option<u32>,
+	/// The queued solution, if any, see [`QueuedSolution`].
+	pub queued_solution: Option<ReadySolution<A>>,
+}
+
 /// Internal errors of the pallet.
 ///
 /// Note that this is different from [`pallet::Error`].
@@ -438,6 +650,15 @@ pub enum ElectionError {
 	OnChainFallback(onchain::Error),
 	/// No fallback is configured. This is a special case.
 	NoFallbackConfigured,
+	/// The result of the election exceeds the bounds given to
+	/// [`ElectionProvider::elect_with_bounds`].
+	BoundsExceeded,
+}
+
+impl From<BoundsExceeded> for ElectionError {
+	fn from(_: BoundsExceeded) -> Self {
+		ElectionError::BoundsExceeded
+	}
 }
 
 impl From<onchain::Error> for ElectionError {
@@ -458,8 +679,20 @@ impl From<unsigned::MinerError> for ElectionError {
 	}
 }
 
+impl sp_std::fmt::Display for ElectionError {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		match self {
+			ElectionError::Feasibility(e) => write!(f, "feasibility error: {}", e),
+			ElectionError::Miner(e) => write!(f, "miner error: {}", e),
+			ElectionError::OnChainFallback(e) => write!(f, "on-chain fallback error: {}", e),
+			ElectionError::NoFallbackConfigured => write!(f, "no fallback configured"),
+			ElectionError::BoundsExceeded => write!(f, "election bounds exceeded"),
+		}
+	}
+}
+
 /// Errors that can happen in the feasibility check.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Encode, Decode)]
 pub enum FeasibilityError {
 	/// Wrong number of winners presented.
 	WrongWinnerCount,
@@ -474,6 +707,16 @@ pub enum FeasibilityError {
 	InvalidVote,
 	/// A voter is invalid.
 	InvalidVoter,
+	/// A voter appears more than once in the solution.
+	DuplicateVoter,
+	/// A voter backs the winners with more than their stake.
+	OverStaked,
+	/// A voter lists the same target more than once in their distribution.
+	DuplicateTargetInDistribution,
+	/// The solution contains more voters or targets than the witness claims.
+	WitnessCompactMismatch,
+	/// A winner is backed by more than [`Config::MaxSupportPerTarget`] of the total stake.
+	SupportCapExceeded,
 	/// A winner is invalid.
 	InvalidWinner,
 	/// The given score was invalid.
@@ -488,6 +731,28 @@ impl From<sp_npos_elections::Error> for FeasibilityError {
 	}
 }
 
+impl sp_std::fmt::Display for FeasibilityError {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		match self {
+			FeasibilityError::WrongWinnerCount => write!(f, "wrong number of winners"),
+			FeasibilityError::SnapshotUnavailable => write!(f, "snapshot unavailable"),
+			FeasibilityError::NposElection(e) => write!(f, "npos elections error: {:?}", e),
+			FeasibilityError::InvalidVote => write!(f, "invalid vote"),
+			FeasibilityError::InvalidVoter => write!(f, "invalid voter"),
+			FeasibilityError::DuplicateVoter => write!(f, "duplicate voter"),
+			FeasibilityError::OverStaked => write!(f, "voter is over-staked"),
+			FeasibilityError::DuplicateTargetInDistribution => {
+				write!(f, "duplicate target in distribution")
+			}
+			FeasibilityError::WitnessCompactMismatch => write!(f, "solution does not match witness"),
+			FeasibilityError::SupportCapExceeded => write!(f, "winner support cap exceeded"),
+			FeasibilityError::InvalidWinner => write!(f, "invalid winner"),
+			FeasibilityError::InvalidScore => write!(f, "invalid score"),
+			FeasibilityError::InvalidRound => write!(f, "invalid round"),
+		}
+	}
+}
+
 pub use pallet::*;
 #[frame_support::pallet]
 pub mod pallet {
@@ -508,17 +773,40 @@ pub mod pallet {
 		/// Duration of the signed phase.
 		#[pallet::constant]
 		type SignedPhase: Get<Self::BlockNumber>;
+		/// Minimum number of blocks to stay in [`Phase::Off`] after a call to `elect`, before any
+		/// new phase can be opened.
+		#[pallet::constant]
+		type PostElectionCooldown: Get<Self::BlockNumber>;
 
-		/// The minimum amount of improvement to the solution score that defines a solution as
-		/// "better" (in any phase).
+		/// The minimum amount of improvement to the solution score that defines a signed solution
+		/// as "better".
 		#[pallet::constant]
 		type SolutionImprovementThreshold: Get<Perbill>;
+		/// The minimum amount of improvement to the solution score that defines an unsigned
+		/// solution as "better" than the queued one.
+		///
+		/// Unsigned solutions are free to submit, thus this is usually larger than
+		/// [`Config::SolutionImprovementThreshold`].
+		#[pallet::constant]
+		type BetterUnsignedThreshold: Get<Perbill>;
+		/// The maximum fraction of the total stake of the snapshot that can back a single winner,
+		/// if any.
+		#[pallet::constant]
+		type MaxSupportPerTarget: Get<Option<Perbill>>;
+		/// The maximum number of voters stored in a single page of the snapshot, see
+		/// [`SnapshotVoters`].
+		#[pallet::constant]
+		type VoterSnapshotPerPage: Get<u32>;
 
 		/// The priority of the unsigned transaction submitted in the unsigned-phase
 		type MinerTxPriority: Get<TransactionPriority>;
+		/// How the priority of an unsigned solution is computed, see [`PriorityStrategy`].
+		type PriorityStrategy: Get<PriorityStrategy>;
 		/// Maximum number of iteration of balancing that will be executed in the embedded miner of
 		/// the pallet.
 		type MinerMaxIterations: Get<u32>;
+		/// The algorithm used by the miner, see [`SolverStrategy`].
+		type MinerSolver: Get<SolverStrategy>;
 		/// Maximum weight that the miner should consume.
 		///
 		/// The miner will ensure that the total weight of the unsigned solution will not exceed
@@ -543,6 +831,14 @@ pub mod pallet {
 		/// Configuration for the fallback
 		type Fallback: Get<FallbackStrategy>;
 
+		/// Number of past rounds for which history is kept, namely the enacted score in
+		/// [`EnactedScores`] and the start blocks of the phases in [`PhaseStartBlocks`].
+		#[pallet::constant]
+		type HistoryDepth: Get<u32>;
+
+		/// Origin that can set the election result during [`Phase::Emergency`].
+		type ForceOrigin: EnsureOrigin<Self::Origin>;
+
 		/// The configuration of benchmarking.
 		type BenchmarkingConfig: BenchmarkingConfig;
 
@@ -555,13 +851,20 @@ pub mod pallet {
 		fn on_initialize(now: T::BlockNumber) -> Weight {
 			let next_election = T::DataProvider::next_election_prediction(now).max(now);
 
-			let signed_deadline = T::SignedPhase::get() + T::UnsignedPhase::get();
-			let unsigned_deadline = T::UnsignedPhase::get();
+			// the deadlines saturate: phases that are together longer than the block number type
+			// can represent simply open as early as possible.
+			let unsigned_deadline = Self::unsigned_phase_length();
+			let signed_deadline = Self::signed_phase_length().saturating_add(unsigned_deadline);
 
-			let remaining = next_election - now;
+			let remaining = next_election.saturating_sub(now);
 			let current_phase = Self::current_phase();
 
-			match current_phase {
+			// `SignedPhaseOverride`, `UnsignedPhaseOverride` and `LastElectionBlock` are read on top
+			// of what the benchmarks of `on_initialize` account for.
+			let additional_reads = T::DbWeight::get().reads(3);
+
+			let weight = match current_phase {
+				Phase::Off if Self::is_cooling_down(now) => T::WeightInfo::on_initialize_nothing(),
 				Phase::Off if remaining <= signed_deadline && remaining > unsigned_deadline => {
 					Self::on_initialize_open_signed();
 					log!(info, "Starting signed phase at #{:?} , round {}.", now, Self::round());
@@ -592,6 +895,16 @@ pub mod pallet {
 					base_weight.saturating_add(additional)
 				}
 				_ => T::WeightInfo::on_initialize_nothing(),
+			};
+			weight.saturating_add(additional_reads)
+		}
+
+		fn on_finalize(now: T::BlockNumber) {
+			// the supports of an election are only needed within the block in which it happened,
+			// and thus never make it into the state. `LastElectionBlock` is already read in
+			// `on_initialize`, and the removal is accounted for by `elect`.
+			if Self::last_election_block() == Some(now) {
+				<ElectedSupports<T>>::kill();
 			}
 		}
 
@@ -599,15 +912,53 @@ pub mod pallet {
 			// We only run the OCW in the first block of the unsigned phase.
 			if Self::current_phase().is_unsigned_open_at(n) {
 				match Self::try_acquire_offchain_lock(n) {
-					Ok(_) => {
+					unsigned::OffchainExecutionDecision::Proceed => {
 						let outcome = Self::mine_check_and_submit().map_err(ElectionError::from);
-						log!(info, "miner exeuction done: {:?}", outcome);
+						let (mined, failed) = Self::offchain_miner_totals();
+						log!(
+							info,
+							"miner exeuction done: {:?}, {} mined and {} failed on this node so far",
+							outcome,
+							mined,
+							failed,
+						);
+					}
+					unsigned::OffchainExecutionDecision::SkipFork => {
+						log!(warn, "denied offchain worker: fork at #{:?}", n)
+					}
+					unsigned::OffchainExecutionDecision::SkipRecent => {
+						log!(warn, "denied offchain worker: recently executed, at #{:?}", n)
+					}
+					unsigned::OffchainExecutionDecision::WriteFailed => {
+						log!(warn, "denied offchain worker: failed to write to offchain db")
 					}
-					Err(why) => log!(warn, "denied offchain worker: {:?}", why),
 				}
 			}
 		}
 
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+			if <StorageVersion<T>>::get() == migrations::Releases::V1_0_0 {
+				weight = weight.saturating_add(migrations::migrate_to_v2::<T>());
+				<StorageVersion<T>>::put(migrations::Releases::V2_0_0);
+			}
+
+			// the pages of the snapshot might not decode anymore with a different
+			// `VoterSnapshotPerPage`. The round continues without a snapshot, i.e. with whatever
+			// solution is already queued, or the fallback.
+			let page_size = Self::snapshot_page_size();
+			let killed = match page_size {
+				Some(size) if size != T::VoterSnapshotPerPage::get() => {
+					let pages = Self::snapshot_pages().unwrap_or_default();
+					log!(warn, "`VoterSnapshotPerPage` changed, killing the snapshot of the round.");
+					Self::kill_snapshot();
+					T::DbWeight::get().reads_writes(3, (pages as Weight).saturating_add(5))
+				}
+				_ => T::DbWeight::get().reads(1),
+			};
+			weight.saturating_add(killed)
+		}
+
 		fn integrity_test() {
 			use sp_std::mem::size_of;
 			// The index type of both voters and targets need to be smaller than that of usize (very
@@ -642,6 +993,18 @@ pub mod pallet {
 			let _: UpperOf<CompactAccuracyOf<T>> = maximum_chain_accuracy
 				.iter()
 				.fold(Zero::zero(), |acc, x| acc.checked_add(x).unwrap());
+
+			// ----------------------------
+			// the snapshot can hold any voter at all.
+			assert!(T::VoterSnapshotPerPage::get() > 0, "VoterSnapshotPerPage must not be zero");
+
+			// ----------------------------
+			// the miner can never produce a solution that does not fit in a block.
+			assert!(
+				T::MinerMaxWeight::get() <= T::BlockWeights::get().max_block,
+				"MinerMaxWeight must not exceed the maximum block weight, see \
+				`Pallet::miner_max_weight_of_block`",
+			);
 		}
 	}
 
@@ -680,24 +1043,151 @@ pub mod pallet {
 			// Check score being an improvement, phase, and desired targets.
 			Self::unsigned_pre_dispatch_checks(&solution).expect(error_message);
 
-			// ensure witness was correct.
-			let SolutionOrSnapshotSize { voters, targets } =
-				Self::snapshot_metadata().expect(error_message);
+			let solution_hash = T::Hashing::hash_of(&solution.compact);
 
+			// ensure witness was correct, and consistent with the solution.
 			// NOTE: we are asserting, not `ensure`ing -- we want to panic here.
-			assert!(voters as u32 == witness.voters, "{}", error_message);
-			assert!(targets as u32 == witness.targets, "{}", error_message);
+			Self::check_witness(&witness).expect(error_message);
+			Self::check_compact_witness(&solution.compact, &witness).expect(error_message);
 
 			let ready =
 				Self::feasibility_check(solution, ElectionCompute::Unsigned).expect(error_message);
 
 			// store the newly received solution.
-			log!(info, "queued unsigned solution with score {:?}", ready.score);
+			log!(info, "queued unsigned solution with score {}", DisplayScore(&ready.score));
 			<QueuedSolution<T>>::put(ready);
+			<SolutionHash<T>>::put(solution_hash);
 			Self::deposit_event(Event::SolutionStored(ElectionCompute::Unsigned));
 
 			Ok(None.into())
 		}
+
+		/// Set the solution to be returned by the next call to `elect`, while in
+		/// [`Phase::Emergency`].
+		///
+		/// The dispatch origin of this call must be [`Config::ForceOrigin`].
+		///
+		/// The given supports are not checked in any way, other than computing their score. They
+		/// are returned as-is by the next call to `elect`, which also ends the emergency phase.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_emergency_solution(
+			origin: OriginFor<T>,
+			supports: Supports<T::AccountId>,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+			ensure!(Self::current_phase().is_emergency(), Error::<T>::CallNotAllowed);
+
+			let score = (&supports).evaluate();
+			log!(info, "queued emergency solution with score {}", DisplayScore(&score));
+			<QueuedSolution<T>>::put(ReadySolution {
+				supports,
+				score,
+				compute: ElectionCompute::Emergency,
+				round: Self::round(),
+			});
+			Self::deposit_event(Event::SolutionStored(ElectionCompute::Emergency));
+
+			Ok(None.into())
+		}
+
+		/// Queue a solution on behalf of governance, e.g. a known-good solution computed off-chain.
+		///
+		/// The dispatch origin of this call must be [`Config::ForceOrigin`].
+		///
+		/// Unlike [`Pallet::submit_unsigned`], an invalid witness or solution results in an error
+		/// rather than a panic. The solution must be for the current round, and it is not allowed
+		/// in [`Phase::Off`]. It replaces the queued solution regardless of their scores, i.e.
+		/// even a worse solution is queued, by intent. The queued solution is marked as
+		/// [`ElectionCompute::Governance`].
+		#[pallet::weight(T::WeightInfo::submit_unsigned(
+			witness.voters,
+			witness.targets,
+			solution.compact.voter_count() as u32,
+			solution.compact.unique_targets().len() as u32
+		))]
+		pub fn set_unsigned_solution(
+			origin: OriginFor<T>,
+			solution: RawSolution<CompactOf<T>>,
+			witness: SolutionOrSnapshotSize,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+			ensure!(!Self::current_phase().is_off(), Error::<T>::CallNotAllowed);
+			ensure!(solution.round == Self::round(), Error::<T>::PreDispatchWrongRound);
+
+			Self::check_witness(&witness)?;
+			Self::check_compact_witness(&solution.compact, &witness)
+				.map_err(|_| Error::<T>::InvalidSolution)?;
+
+			let solution_hash = T::Hashing::hash_of(&solution.compact);
+			let ready =
+				Self::feasibility_check(solution, ElectionCompute::Governance).map_err(|e| {
+					log!(warn, "governance solution is not feasible: {:?}", e);
+					Error::<T>::InvalidSolution
+				})?;
+
+			// no score check: governance may well replace the queued solution with a worse one.
+			log!(info, "queued governance solution with score {}", DisplayScore(&ready.score));
+			<QueuedSolution<T>>::put(ready);
+			<SolutionHash<T>>::put(solution_hash);
+			Self::deposit_event(Event::SolutionStored(ElectionCompute::Governance));
+
+			Ok(None.into())
+		}
+
+		/// Abandon a round that is stuck, e.g. because `elect` was never called: clear all of its
+		/// data, i.e. the snapshot and the queued solution, and go back to [`Phase::Off`].
+		///
+		/// The round counter is not incremented, the next phase opens the same round afresh.
+		///
+		/// The dispatch origin of this call must be [`Config::ForceOrigin`]. Note that it is
+		/// allowed in any phase **other than** [`Phase::Off`]: a round that is stuck is in the
+		/// signed, unsigned or emergency phase, while [`Phase::Off`] has no round data to clear.
+		///
+		/// `snapshot_pages` must be at least the number of pages of the snapshot, as returned by
+		/// [`Pallet::snapshot_pages`]. The weight of the call is based on it.
+		#[pallet::weight(
+			T::DbWeight::get().reads_writes(3, (*snapshot_pages as Weight).saturating_add(8))
+		)]
+		pub fn clear_round_data(
+			origin: OriginFor<T>,
+			snapshot_pages: u32,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+			ensure!(!Self::current_phase().is_off(), Error::<T>::CallNotAllowed);
+			ensure!(
+				Self::snapshot_pages().unwrap_or_default() <= snapshot_pages,
+				Error::<T>::WitnessWrongPageCount,
+			);
+
+			log!(warn, "clearing round data of round {}.", Self::round());
+			<CurrentPhase<T>>::put(Phase::Off);
+			Self::kill_snapshot();
+			<QueuedSolution<T>>::kill();
+			<SolutionHash<T>>::kill();
+
+			Ok(None.into())
+		}
+
+		/// Override the lengths of the signed and unsigned phases, or revert to
+		/// [`Config::SignedPhase`] and [`Config::UnsignedPhase`] with `None`.
+		///
+		/// The dispatch origin of this call must be [`Config::ForceOrigin`].
+		///
+		/// The overrides only take effect once the ongoing round, if any, is over, i.e. the next
+		/// time the pallet enters [`Phase::Off`] after a call to `elect`.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_phase_overrides(
+			origin: OriginFor<T>,
+			signed: Option<T::BlockNumber>,
+			unsigned: Option<T::BlockNumber>,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			log!(info, "scheduled phase overrides: signed {:?}, unsigned {:?}", signed, unsigned);
+			<PendingPhaseOverrides<T>>::put((signed, unsigned));
+
+			Ok(None.into())
+		}
 	}
 
 	#[pallet::event]
@@ -731,6 +1221,22 @@ pub mod pallet {
 		PreDispatchWrongWinnerCount,
 		/// Submission was too weak, score-wise.
 		PreDispatchWeakSubmission,
+		/// The snapshot metadata needed to check the witness is not available.
+		WitnessSnapshotUnavailable,
+		/// The witness data has a wrong number of voters.
+		WitnessWrongVoterCount,
+		/// The witness data has a wrong number of targets.
+		WitnessWrongTargetCount,
+		/// Submission is identical to the one already queued.
+		PreDispatchDuplicateSubmission,
+		/// The call is not allowed in the current phase.
+		CallNotAllowed,
+		/// The submitted solution is not feasible.
+		InvalidSolution,
+		/// Submission is for another round than the current one.
+		PreDispatchWrongRound,
+		/// The witness data has fewer pages than the snapshot.
+		WitnessWrongPageCount,
 	}
 
 	#[pallet::origin]
@@ -756,17 +1262,12 @@ pub mod pallet {
 					.map_err(dispatch_error_to_invalid)?;
 
 				ValidTransaction::with_tag_prefix("OffchainElection")
-					// The higher the score[0], the better a solution is.
-					.priority(
-						T::MinerTxPriority::get().saturating_add(
-							solution.score[0].saturated_into()
-						),
-					)
+					.priority(Self::unsigned_priority(&solution.score))
 					// used to deduplicate unsigned solutions: each validator should produce one
 					// solution per round at most, and solutions are not propagate.
 					.and_provides(solution.round)
 					// transaction should stay in the pool for the duration of the unsigned phase.
-					.longevity(T::UnsignedPhase::get().saturated_into::<u64>())
+					.longevity(Self::unsigned_phase_length().saturated_into::<u64>())
 					// We don't propagate this. This can never be validated at a remote node.
 					.propagate(false)
 					.build()
@@ -811,27 +1312,118 @@ pub mod pallet {
 	#[pallet::getter(fn queued_solution)]
 	pub type QueuedSolution<T: Config> = StorageValue<_, ReadySolution<T::AccountId>>;
 
-	/// Snapshot data of the round.
+	/// Hash of the compact solution from which [`QueuedSolution`] was built.
+	///
+	/// Used to cheaply reject re-submissions of the queued solution. Only exists when
+	/// [`QueuedSolution`] was stored via a submission.
+	#[pallet::storage]
+	#[pallet::getter(fn queued_solution_hash)]
+	pub type SolutionHash<T: Config> = StorageValue<_, T::Hash>;
+
+	/// The block number at which `elect` was last called.
+	///
+	/// Used to enforce [`Config::PostElectionCooldown`].
+	#[pallet::storage]
+	#[pallet::getter(fn last_election_block)]
+	pub type LastElectionBlock<T: Config> = StorageValue<_, T::BlockNumber>;
+
+	/// The supports returned by the last successful `elect`.
+	///
+	/// Returned again if `elect` is called more than once in the block of
+	/// [`LastElectionBlock`]. Removed in the `on_finalize` of that same block, thus never part of
+	/// the state between blocks.
+	#[pallet::storage]
+	pub type ElectedSupports<T: Config> = StorageValue<_, Supports<T::AccountId>>;
+
+	/// The length of the signed phase to use instead of [`Config::SignedPhase`], if any.
+	#[pallet::storage]
+	#[pallet::getter(fn signed_phase_override)]
+	pub type SignedPhaseOverride<T: Config> = StorageValue<_, T::BlockNumber>;
+
+	/// The length of the unsigned phase to use instead of [`Config::UnsignedPhase`], if any.
+	#[pallet::storage]
+	#[pallet::getter(fn unsigned_phase_override)]
+	pub type UnsignedPhaseOverride<T: Config> = StorageValue<_, T::BlockNumber>;
+
+	/// The `(signed, unsigned)` phase overrides set via [`Pallet::set_phase_overrides`], to be
+	/// applied at the end of the ongoing round.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_phase_overrides)]
+	pub type PendingPhaseOverrides<T: Config> =
+		StorageValue<_, (Option<T::BlockNumber>, Option<T::BlockNumber>)>;
+
+	/// The compute of the last successful election.
+	#[pallet::storage]
+	#[pallet::getter(fn last_election_compute)]
+	pub type LastElectionCompute<T: Config> = StorageValue<_, ElectionCompute>;
+
+	/// The score and compute of the enacted solution of the last [`Config::HistoryDepth`] rounds.
+	///
+	/// Keyed by round. Only rounds in which the election was successfully finalized are present.
+	#[pallet::storage]
+	#[pallet::getter(fn enacted_scores)]
+	pub type EnactedScores<T: Config> =
+		StorageMap<_, Twox64Concat, u32, (ElectionScore, ElectionCompute)>;
+
+	/// The blocks at which the phases of the last [`Config::HistoryDepth`] rounds started.
+	///
+	/// Keyed by round.
+	#[pallet::storage]
+	#[pallet::getter(fn phase_starts)]
+	pub type PhaseStartBlocks<T: Config> =
+		StorageMap<_, Twox64Concat, u32, PhaseStarts<T::BlockNumber>, ValueQuery>;
+
+	/// The targets of the snapshot of the round, along with the number of targets to elect as of
+	/// the time the snapshot was taken.
+	///
+	/// This is created at the beginning of the signed phase and cleared upon calling `elect`. The
+	/// entire snapshot can be read via [`Pallet::snapshot`].
+	#[pallet::storage]
+	pub type SnapshotTargets<T: Config> = StorageValue<_, (TargetList<T>, u32)>;
+
+	/// The voters of the snapshot of the round, split into pages of at most
+	/// [`Config::VoterSnapshotPerPage`] voters each.
+	///
+	/// Only exists when [`SnapshotTargets`] is present.
+	#[pallet::storage]
+	pub type SnapshotVoters<T: Config> = StorageMap<_, Twox64Concat, u32, VoterPage<T>>;
+
+	/// The number of pages in [`SnapshotVoters`].
+	///
+	/// Only exists when [`SnapshotTargets`] is present.
+	#[pallet::storage]
+	#[pallet::getter(fn snapshot_pages)]
+	pub type SnapshotPages<T: Config> = StorageValue<_, u32>;
+
+	/// The [`Config::VoterSnapshotPerPage`] that [`SnapshotVoters`] was split with.
 	///
-	/// This is created at the beginning of the signed phase and cleared upon calling `elect`.
+	/// Only exists when [`SnapshotTargets`] is present.
 	#[pallet::storage]
-	#[pallet::getter(fn snapshot)]
-	pub type Snapshot<T: Config> = StorageValue<_, RoundSnapshot<T::AccountId>>;
+	#[pallet::getter(fn snapshot_page_size)]
+	pub type SnapshotPageSize<T: Config> = StorageValue<_, u32>;
 
-	/// Desired number of targets to elect for this round.
+	/// The total vote weight of the voters of the snapshot, see
+	/// [`RoundSnapshot::total_vote_weight`].
 	///
-	/// Only exists when [`Snapshot`] is present.
+	/// Only exists when [`SnapshotTargets`] is present.
 	#[pallet::storage]
-	#[pallet::getter(fn desired_targets)]
-	pub type DesiredTargets<T> = StorageValue<_, u32>;
+	#[pallet::getter(fn snapshot_total_stake)]
+	pub type SnapshotTotalStake<T: Config> = StorageValue<_, ExtendedBalance>;
 
 	/// The metadata of the [`RoundSnapshot`]
 	///
-	/// Only exists when [`Snapshot`] is present.
+	/// Only exists when [`SnapshotTargets`] is present.
 	#[pallet::storage]
 	#[pallet::getter(fn snapshot_metadata)]
 	pub type SnapshotMetadata<T: Config> = StorageValue<_, SolutionOrSnapshotSize>;
 
+	/// Storage version of the pallet.
+	///
+	/// This is [`migrations::Releases::V1_0_0`] for networks that have not been migrated yet.
+	#[pallet::storage]
+	pub(super) type StorageVersion<T: Config> =
+		StorageValue<_, migrations::Releases, ValueQuery>;
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(PhantomData<T>);
@@ -843,6 +1435,9 @@ impl<T: Config> Pallet<T> {
 	/// This is decoupled for easy weight calculation.
 	pub(crate) fn on_initialize_open_signed() {
 		<CurrentPhase<T>>::put(Phase::Signed);
+		Self::note_phase_start(|starts| {
+			starts.signed = Some(<frame_system::Pallet<T>>::block_number())
+		});
 		Self::create_snapshot();
 		Self::deposit_event(Event::SignedPhaseStarted(Self::round()));
 	}
@@ -863,33 +1458,260 @@ impl<T: Config> Pallet<T> {
 		}
 
 		<CurrentPhase<T>>::put(Phase::Unsigned((enabled, now)));
+		Self::note_phase_start(|starts| starts.unsigned = Some(now));
 		Self::deposit_event(Event::UnsignedPhaseStarted(Self::round()));
 	}
 
 	/// Creates the snapshot. Writes new data to:
 	///
 	/// 1. [`SnapshotMetadata`]
-	/// 2. [`RoundSnapshot`]
-	/// 3. [`DesiredTargets`]
+	/// 2. [`SnapshotTargets`], [`SnapshotVoters`] and [`SnapshotPages`]
+	///
+	/// The voters are sorted by account id, such that the snapshot (and everything derived from
+	/// it, such as trimming in the miner) does not depend on the order of the data provider.
 	pub(crate) fn create_snapshot() {
 		// if any of them don't exist, create all of them. This is a bit conservative.
 		let targets = T::DataProvider::targets();
-		let voters = T::DataProvider::voters();
+		let mut voters = T::DataProvider::voters();
+		voters.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
 		let desired_targets = T::DataProvider::desired_targets();
+		if desired_targets as usize > targets.len() {
+			// not an error, see `desired_winners`, but most likely a bug in the data provider.
+			log!(
+				warn,
+				"desired targets ({}) exceeds the number of targets ({}), all will be elected.",
+				desired_targets,
+				targets.len(),
+			);
+		}
+
+		Self::put_snapshot(RoundSnapshot { voters, targets, desired_targets });
+	}
+
+	/// Write the given snapshot to storage, splitting the voters into pages of
+	/// [`Config::VoterSnapshotPerPage`].
+	///
+	/// Targets that a [`Config::CompactSolution`] cannot index, and votes that it cannot hold, are
+	/// dropped.
+	pub(crate) fn put_snapshot(mut snapshot: RoundSnapshot<T::AccountId>) {
+		let max_targets = Self::max_snapshot_targets() as usize;
+		let targets = &mut snapshot.targets;
+		if targets.len() > max_targets {
+			log!(warn, "dropping {} targets that cannot be indexed.", targets.len() - max_targets);
+			targets.truncate(max_targets);
+		}
+		let max_votes = Self::max_votes_per_voter() as usize;
+		for (who, _, votes) in snapshot.voters.iter_mut().filter(|(_, _, v)| v.len() > max_votes) {
+			log!(warn, "dropping {} votes of voter {:?}.", votes.len() - max_votes, who);
+			votes.truncate(max_votes);
+		}
 
 		<SnapshotMetadata<T>>::put(SolutionOrSnapshotSize {
-			voters: voters.len() as u32,
-			targets: targets.len() as u32,
+			voters: snapshot.voters.len() as u32,
+			targets: snapshot.targets.len() as u32,
 		});
-		<DesiredTargets<T>>::put(desired_targets);
-		<Snapshot<T>>::put(RoundSnapshot { voters, targets });
+		<SnapshotTotalStake<T>>::put(snapshot.total_vote_weight());
+
+		let RoundSnapshot { voters, targets, desired_targets } = snapshot;
+		let per_page = T::VoterSnapshotPerPage::get().max(1);
+		let mut pages = 0u32;
+		for page in voters.chunks(per_page as usize) {
+			<SnapshotVoters<T>>::insert(pages, VoterPage(page.to_vec()));
+			pages += 1;
+		}
+		<SnapshotPages<T>>::put(pages);
+		<SnapshotPageSize<T>>::put(per_page);
+		<SnapshotTargets<T>>::put((TargetList(targets), desired_targets));
+	}
+
+	/// The maximum number of targets in the snapshot, i.e. the number of targets that a
+	/// [`Config::CompactSolution`] can index.
+	pub fn max_snapshot_targets() -> u32 {
+		let bits = 8 * sp_std::mem::size_of::<CompactTargetIndexOf<T>>();
+		if bits >= 32 {
+			u32::max_value()
+		} else {
+			1 << bits
+		}
+	}
+
+	/// The maximum number of votes of a voter in the snapshot, i.e. the number of votes that a
+	/// [`Config::CompactSolution`] can hold.
+	pub fn max_votes_per_voter() -> u32 {
+		<CompactOf<T> as CompactSolution>::LIMIT as u32
+	}
+
+	/// The entire snapshot of the round, if any, assembled from all of its pages.
+	///
+	/// Returns `None` if the snapshot, or any of its pages, does not exist.
+	pub fn snapshot() -> Option<RoundSnapshot<T::AccountId>> {
+		let (TargetList(targets), desired_targets) = <SnapshotTargets<T>>::get()?;
+		let pages = Self::snapshot_pages()?;
+		let mut voters = Vec::new();
+		for page in 0..pages {
+			voters.extend(<SnapshotVoters<T>>::get(page)?.0);
+		}
+		Some(RoundSnapshot { voters, targets, desired_targets })
+	}
+
+	/// The voters of the snapshot at `page`, when read in pages of `page_size` voters.
+	///
+	/// Only the storage pages of [`SnapshotVoters`] that overlap with the requested page are read,
+	/// so this can be used to fetch a large snapshot incrementally. Returns an empty vector if
+	/// there is no snapshot, or `page` is past its end.
+	pub fn snapshot_voters_paged(
+		page: u32,
+		page_size: u32,
+	) -> Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)> {
+		let total = match Self::snapshot_metadata() {
+			Some(SolutionOrSnapshotSize { voters, .. }) => voters as usize,
+			None => return Vec::new(),
+		};
+		let start = (page as usize).saturating_mul(page_size as usize);
+		let end = start.saturating_add(page_size as usize).min(total);
+		if start >= end {
+			return Vec::new();
+		}
+
+		// the size of the storage pages as of the creation of the snapshot. If
+		// `VoterSnapshotPerPage` changes, the snapshot is killed in `on_runtime_upgrade`.
+		let per_page = match Self::snapshot_page_size() {
+			Some(size) => size.max(1) as usize,
+			None => return Vec::new(),
+		};
+
+		let mut voters = Vec::with_capacity(end - start);
+		for stored in (start / per_page)..=((end - 1) / per_page) {
+			let stored_voters =
+				<SnapshotVoters<T>>::get(stored as u32).map(|p| p.0).unwrap_or_default();
+			let offset = stored * per_page;
+			voters.extend(
+				stored_voters.into_iter().take(end - offset).skip(start.saturating_sub(offset)),
+			);
+		}
+		voters
+	}
+
+	/// The targets of the snapshot at `page`, when read in pages of `page_size` targets.
+	///
+	/// Returns an empty vector if there is no snapshot, or `page` is past its end.
+	pub fn snapshot_targets_paged(page: u32, page_size: u32) -> Vec<T::AccountId> {
+		let targets = <SnapshotTargets<T>>::get().map(|(targets, _)| targets.0).unwrap_or_default();
+		let start = (page as usize).saturating_mul(page_size as usize);
+		targets.into_iter().skip(start).take(page_size as usize).collect()
+	}
+
+	/// The number of targets to elect in this round, as stored in the snapshot, if any.
+	pub fn desired_targets() -> Option<u32> {
+		<SnapshotTargets<T>>::get().map(|(_, desired_targets)| desired_targets)
 	}
 
 	/// Kill everything created by [`Pallet::create_snapshot`].
 	pub(crate) fn kill_snapshot() {
-		<Snapshot<T>>::kill();
+		for page in 0..Self::snapshot_pages().unwrap_or_default() {
+			<SnapshotVoters<T>>::remove(page);
+		}
+		<SnapshotPages<T>>::kill();
+		<SnapshotPageSize<T>>::kill();
+		<SnapshotTotalStake<T>>::kill();
+		<SnapshotTargets<T>>::kill();
 		<SnapshotMetadata<T>>::kill();
-		<DesiredTargets<T>>::kill();
+	}
+
+	/// Dry-run the feasibility check of `solution` against the current snapshot.
+	///
+	/// Returns the score of the solution if it is feasible, or the reason for which it is not.
+	/// Nothing is written to storage, the solution is not queued.
+	pub fn check_solution(
+		solution: RawSolution<CompactOf<T>>,
+	) -> Result<ElectionScore, FeasibilityError> {
+		Self::feasibility_check(solution, ElectionCompute::Signed).map(|ready| ready.score)
+	}
+
+	/// Export the entire state of the current round in one go.
+	///
+	/// All items are read in the same call, thus they are consistent with one another.
+	pub fn export_round_state() -> RoundStateExport<T::AccountId, T::BlockNumber> {
+		RoundStateExport {
+			round: Self::round(),
+			phase: Self::current_phase(),
+			snapshot: Self::snapshot(),
+			snapshot_metadata: Self::snapshot_metadata(),
+			desired_targets: Self::desired_targets(),
+			queued_solution: Self::queued_solution(),
+		}
+	}
+
+	/// The estimated number of blocks until the next election, as predicted by
+	/// [`ElectionDataProvider::next_election_prediction`].
+	pub fn blocks_until_next_election() -> T::BlockNumber {
+		let now = <frame_system::Pallet<T>>::block_number();
+		T::DataProvider::next_election_prediction(now).saturating_sub(now)
+	}
+
+	/// The backing of `target` in the currently queued solution, if any.
+	///
+	/// Returns the total backing and the individual backers with their stake, or `None` if there is
+	/// no queued solution or `target` is not one of its winners.
+	pub fn queued_support_of(
+		target: &T::AccountId,
+	) -> Option<(ExtendedBalance, Vec<(T::AccountId, ExtendedBalance)>)> {
+		Self::queued_solution().and_then(|ReadySolution { supports, .. }| {
+			supports
+				.into_iter()
+				.find(|(winner, _)| winner == target)
+				.map(|(_, support)| (support.total, support.voters))
+		})
+	}
+
+	/// The relative improvement of the first element of `score` (the minimal backing) over that of
+	/// the queued solution.
+	///
+	/// Returns `None` if there is no queued solution. A score that does not improve upon the queued
+	/// one yields zero, and improvements beyond 100% are saturated.
+	pub fn score_improvement_over_queued(score: ElectionScore) -> Option<Perbill> {
+		Self::queued_solution().map(|ReadySolution { score: queued, .. }| {
+			Perbill::from_rational_approximation(score[0].saturating_sub(queued[0]), queued[0])
+		})
+	}
+
+	/// The exact number of winners that a solution must have, given `desired_targets` and the
+	/// number of targets in the snapshot.
+	///
+	/// If there are fewer targets than desired, the best any solution can do is to elect all of
+	/// them.
+	pub(crate) fn desired_winners(desired_targets: u32, snapshot_targets: u32) -> u32 {
+		desired_targets.min(snapshot_targets)
+	}
+
+	/// Checks that the given witness data matches the size of the current snapshot.
+	///
+	/// This is the single place where witness data of any submission is validated.
+	pub(crate) fn check_witness(witness: &SolutionOrSnapshotSize) -> DispatchResult {
+		let SolutionOrSnapshotSize { voters, targets } =
+			Self::snapshot_metadata().ok_or(Error::<T>::WitnessSnapshotUnavailable)?;
+
+		ensure!(voters == witness.voters, Error::<T>::WitnessWrongVoterCount);
+		ensure!(targets == witness.targets, Error::<T>::WitnessWrongTargetCount);
+
+		Ok(())
+	}
+
+	/// Checks that `compact` does not contain more voters or targets than `witness` claims are in
+	/// the snapshot.
+	pub(crate) fn check_compact_witness(
+		compact: &CompactOf<T>,
+		witness: &SolutionOrSnapshotSize,
+	) -> Result<(), FeasibilityError> {
+		ensure!(
+			compact.voter_count() <= witness.voters as usize,
+			FeasibilityError::WitnessCompactMismatch,
+		);
+		ensure!(
+			compact.unique_targets().len() <= witness.targets as usize,
+			FeasibilityError::WitnessCompactMismatch,
+		);
+		Ok(())
 	}
 
 	/// Checks the feasibility of a solution.
@@ -905,23 +1727,22 @@ impl<T: Config> Pallet<T> {
 		// winners are not directly encoded in the solution.
 		let winners = compact.unique_targets();
 
-		let desired_targets =
-			Self::desired_targets().ok_or(FeasibilityError::SnapshotUnavailable)?;
+		// read the targets, the voters are only read from the pages that the solution refers to.
+		let (TargetList(snapshot_targets), desired_targets) =
+			<SnapshotTargets<T>>::get().ok_or(FeasibilityError::SnapshotUnavailable)?;
+		let SolutionOrSnapshotSize { voters: voter_count, .. } =
+			Self::snapshot_metadata().ok_or(FeasibilityError::SnapshotUnavailable)?;
+		let page_size =
+			Self::snapshot_page_size().ok_or(FeasibilityError::SnapshotUnavailable)?.max(1);
 
 		// NOTE: this is a bit of duplicate, but we keep it around for veracity. The unsigned path
 		// already checked this in `unsigned_per_dispatch_checks`. The signed path *could* check it
 		// upon arrival, thus we would then remove it here. Given overlay it is cheap anyhow
-		ensure!(winners.len() as u32 == desired_targets, FeasibilityError::WrongWinnerCount);
-
-		// read the entire snapshot.
-		let RoundSnapshot { voters: snapshot_voters, targets: snapshot_targets } =
-			Self::snapshot().ok_or(FeasibilityError::SnapshotUnavailable)?;
+		let desired_winners = Self::desired_winners(desired_targets, snapshot_targets.len() as u32);
+		ensure!(winners.len() as u32 == desired_winners, FeasibilityError::WrongWinnerCount);
 
 		// ----- Start building. First, we need some closures.
-		let cache = helpers::generate_voter_cache::<T>(&snapshot_voters);
-		let voter_at = helpers::voter_at_fn::<T>(&snapshot_voters);
 		let target_at = helpers::target_at_fn::<T>(&snapshot_targets);
-		let voter_index = helpers::voter_index_fn_usize::<T>(&cache);
 
 		// first, make sure that all the winners are sane.
 		// OPTIMIZATION: we could first build the assignments, and then extract the winners directly
@@ -933,58 +1754,158 @@ impl<T: Config> Pallet<T> {
 			.map(|i| target_at(i).ok_or(FeasibilityError::InvalidWinner))
 			.collect::<Result<Vec<T::AccountId>, FeasibilityError>>()?;
 
-		// Then convert compact -> assignment. This will fail if any of the indices are gibberish.
-		let assignments = compact
-			.into_assignment(voter_at, target_at)
+		// Then convert compact -> assignment of indices. This will fail if any of the indices are
+		// gibberish.
+		let index_within = |index: usize, count: usize| Some(index as u32).filter(|_| index < count);
+		let indexed_assignments = compact
+			.into_assignment(
+				|v| {
+					<CompactVoterIndexOf<T> as TryInto<usize>>::try_into(v)
+						.ok()
+						.and_then(|v| index_within(v, voter_count as usize))
+				},
+				|t| {
+					<CompactTargetIndexOf<T> as TryInto<usize>>::try_into(t)
+						.ok()
+						.and_then(|t| index_within(t, snapshot_targets.len()))
+				},
+			)
 			.map_err::<FeasibilityError, _>(Into::into)?;
 
+		// read the pages of the voters of the solution, each one once.
+		let mut pages = BTreeMap::new();
+		for assignment in indexed_assignments.iter() {
+			let page = assignment.who / page_size;
+			if !pages.contains_key(&page) {
+				let voters = <SnapshotVoters<T>>::get(page)
+					.ok_or(FeasibilityError::SnapshotUnavailable)?;
+				pages.insert(page, voters.0);
+			}
+		}
+		let voter_at_index = |index: u32| {
+			pages.get(&(index / page_size)).and_then(|page| page.get((index % page_size) as usize))
+		};
+
 		// Ensure that assignments is correct.
-		let _ = assignments
-			.iter()
-			.map(|ref assignment| {
-				// check that assignment.who is actually a voter (defensive-only).
-				// NOTE: while using the index map from `voter_index` is better than a blind linear
-				// search, this *still* has room for optimization. Note that we had the index when
-				// we did `compact -> assignment` and we lost it. Ideal is to keep the index around.
-
-				// defensive-only: must exist in the snapshot.
-				let snapshot_index =
-					voter_index(&assignment.who).ok_or(FeasibilityError::InvalidVoter)?;
+		let mut seen_voters = BTreeSet::new();
+		let mut stakes = BTreeMap::new();
+		let assignments = indexed_assignments
+			.into_iter()
+			.map(|assignment| {
 				// defensive-only: index comes from the snapshot, must exist.
-				let (_voter, _stake, targets) =
-					snapshot_voters.get(snapshot_index).ok_or(FeasibilityError::InvalidVoter)?;
+				let (voter, stake, targets) =
+					voter_at_index(assignment.who).ok_or(FeasibilityError::InvalidVoter)?;
+
+				// each voter can only appear once, else their stake is counted more than once.
+				if !seen_voters.insert(voter.clone()) {
+					return Err(FeasibilityError::DuplicateVoter);
+				}
+				stakes.insert(voter.clone(), *stake);
+
+				// defensive-only: indices come from the snapshot, must exist.
+				let distribution = assignment
+					.distribution
+					.into_iter()
+					.map(|(t, p)| Some((snapshot_targets.get(t as usize)?.clone(), p)))
+					.collect::<Option<Vec<_>>>()
+					.ok_or(FeasibilityError::InvalidVote)?;
 
 				// check that all of the targets are valid based on the snapshot.
-				if assignment.distribution.iter().any(|(d, _)| !targets.contains(d)) {
+				if distribution.iter().any(|(d, _)| !targets.contains(d)) {
 					return Err(FeasibilityError::InvalidVote);
 				}
-				Ok(())
+
+				// each target can only appear once in the distribution of a voter, else the stake
+				// given to it is counted more than once. Distributions are short, a linear search
+				// is fine.
+				if distribution
+					.iter()
+					.enumerate()
+					.any(|(i, (d, _))| distribution[..i].iter().any(|(other, _)| other == d))
+				{
+					return Err(FeasibilityError::DuplicateTargetInDistribution);
+				}
+				Ok(Assignment { who: voter.clone(), distribution })
 			})
-			.collect::<Result<(), FeasibilityError>>()?;
+			.collect::<Result<Vec<_>, FeasibilityError>>()?;
 
 		// ----- Start building support. First, we need one more closure.
-		let stake_of = helpers::stake_of_fn::<T>(&snapshot_voters, &cache);
+		let stake_of =
+			|who: &T::AccountId| -> VoteWeight { stakes.get(who).cloned().unwrap_or_default() };
 
 		// This might fail if the normalization fails. Very unlikely. See `integrity_test`.
-		let staked_assignments = assignment_ratio_to_staked_normalized(assignments, stake_of)
+		let staked_assignments = assignment_ratio_to_staked_normalized(assignments, &stake_of)
 			.map_err::<FeasibilityError, _>(Into::into)?;
 
+		// defensive-only: no voter can back the winners with more than their stake, regardless of
+		// any rounding in the accuracy of the compact.
+		let over_staked = staked_assignments.iter().any(|assignment| {
+			let backing = assignment
+				.distribution
+				.iter()
+				.fold(Zero::zero(), |acc: ExtendedBalance, (_, w)| acc.saturating_add(*w));
+			backing > stake_of(&assignment.who) as ExtendedBalance
+		});
+		ensure!(!over_staked, FeasibilityError::OverStaked);
+
 		// This might fail if one of the voter edges is pointing to a non-winner, which is not
 		// really possible anymore because all the winners come from the same `compact`.
 		let supports = sp_npos_elections::to_supports(&winners, &staked_assignments)
 			.map_err::<FeasibilityError, _>(Into::into)?;
 
+		// no winner can be backed by more than the allowed fraction of the total stake, if any.
+		if let Some(max_support) = T::MaxSupportPerTarget::get() {
+			let total_stake =
+				Self::snapshot_total_stake().ok_or(FeasibilityError::SnapshotUnavailable)?;
+			let cap = max_support * total_stake;
+			ensure!(
+				supports.iter().all(|(_, support)| support.total <= cap),
+				FeasibilityError::SupportCapExceeded,
+			);
+		}
+
 		// Finally, check that the claimed score was indeed correct.
 		let known_score = (&supports).evaluate();
 		ensure!(known_score == score, FeasibilityError::InvalidScore);
 
-		Ok(ReadySolution { supports, compute, score })
+		Ok(ReadySolution { supports, compute, score, round })
+	}
+
+	/// A safe value for [`Config::MinerMaxWeight`]: `ratio` of the maximum block weight, minus the
+	/// base weight of the block itself and of the unsigned extrinsic.
+	///
+	/// Runtimes can use this in a `parameter_types!` declaration, rather than deriving the value by
+	/// hand.
+	pub fn miner_max_weight_of_block(ratio: Perbill) -> Weight {
+		let weights = T::BlockWeights::get();
+		(ratio * weights.max_block)
+			.saturating_sub(weights.base_block)
+			.saturating_sub(weights.get(DispatchClass::Normal).base_extrinsic)
+	}
+
+	/// The length of the signed phase, i.e. [`SignedPhaseOverride`] if set, else
+	/// [`Config::SignedPhase`].
+	pub fn signed_phase_length() -> T::BlockNumber {
+		Self::signed_phase_override().unwrap_or_else(T::SignedPhase::get)
+	}
+
+	/// The length of the unsigned phase, i.e. [`UnsignedPhaseOverride`] if set, else
+	/// [`Config::UnsignedPhase`].
+	pub fn unsigned_phase_length() -> T::BlockNumber {
+		Self::unsigned_phase_override().unwrap_or_else(T::UnsignedPhase::get)
+	}
+
+	/// Returns `true` if we are still within [`Config::PostElectionCooldown`] blocks of the last
+	/// call to `elect`.
+	fn is_cooling_down(now: T::BlockNumber) -> bool {
+		Self::last_election_block()
+			.map_or(false, |last| now < last.saturating_add(T::PostElectionCooldown::get()))
 	}
 
 	/// Perform the tasks to be done after a new `elect` has been triggered:
 	///
 	/// 1. Increment round.
-	/// 2. Change phase to [`Phase::Off`]
+	/// 2. Change phase to [`Phase::Off`], and record the block number.
 	/// 3. Clear all snapshot data.
 	fn post_elect() {
 		// inc round
@@ -992,9 +1913,22 @@ impl<T: Config> Pallet<T> {
 
 		// change phase
 		<CurrentPhase<T>>::put(Phase::Off);
+		<LastElectionBlock<T>>::put(<frame_system::Pallet<T>>::block_number());
+
+		// the queued solution is already taken in `do_elect`.
+		<SolutionHash<T>>::kill();
 
 		// kill snapshots
 		Self::kill_snapshot();
+
+		// let the data provider release whatever it kept for this election.
+		T::DataProvider::on_election_completed();
+
+		// the round is over, it is now safe to change the phase lengths.
+		if let Some((signed, unsigned)) = <PendingPhaseOverrides<T>>::take() {
+			<SignedPhaseOverride<T>>::set(signed);
+			<UnsignedPhaseOverride<T>>::set(unsigned);
+		}
 	}
 
 	/// On-chain fallback of election.
@@ -1006,19 +1940,53 @@ impl<T: Config> Pallet<T> {
 		.map_err(Into::into)
 	}
 
+	/// Record the score of the solution enacted in the current round in [`EnactedScores`], and
+	/// prune the entry that is now older than [`Config::HistoryDepth`].
+	fn note_enacted_score(score: ElectionScore, compute: ElectionCompute) {
+		let round = Self::round();
+		<EnactedScores<T>>::insert(round, (score, compute));
+		if let Some(stale) = round.checked_sub(T::HistoryDepth::get()) {
+			<EnactedScores<T>>::remove(stale);
+		}
+	}
+
+	/// Record the start of a phase of the current round in [`PhaseStartBlocks`], and prune the
+	/// entry that is now older than [`Config::HistoryDepth`].
+	fn note_phase_start(f: impl FnOnce(&mut PhaseStarts<T::BlockNumber>)) {
+		let round = Self::round();
+		<PhaseStartBlocks<T>>::mutate(round, f);
+		if let Some(stale) = round.checked_sub(T::HistoryDepth::get()) {
+			<PhaseStartBlocks<T>>::remove(stale);
+		}
+	}
+
 	fn do_elect() -> Result<Supports<T::AccountId>, ElectionError> {
 		<QueuedSolution<T>>::take()
+			.filter(|ready| {
+				// defensive-only: a solution of another round was left behind, and does not match
+				// the snapshot of this round.
+				let current = ready.round == Self::round();
+				if !current {
+					log!(warn, "ignoring queued solution of round {}", ready.round);
+				}
+				current
+			})
 			.map_or_else(
 				|| match T::Fallback::get() {
 					FallbackStrategy::OnChain => Self::onchain_fallback()
-						.map(|r| (r, ElectionCompute::OnChain))
+						.map(|supports| {
+							let score = (&supports).evaluate();
+							(supports, score, ElectionCompute::OnChain)
+						})
 						.map_err(Into::into),
 					FallbackStrategy::Nothing => Err(ElectionError::NoFallbackConfigured),
 				},
-				|ReadySolution { supports, compute, .. }| Ok((supports, compute)),
+				|ReadySolution { supports, score, compute, .. }| Ok((supports, score, compute)),
 			)
-			.map(|(supports, compute)| {
+			.map(|(supports, score, compute)| {
 				Self::deposit_event(Event::ElectionFinalized(Some(compute)));
+				Self::note_enacted_score(score, compute);
+				<LastElectionCompute<T>>::put(compute);
 				log!(info, "Finalized election round with compute {:?}.", compute);
 				supports
 			})
@@ -1035,8 +2003,33 @@ impl<T: Config> ElectionProvider<T::AccountId, T::BlockNumber> for Pallet<T> {
 	type DataProvider = T::DataProvider;
 
 	fn elect() -> Result<Supports<T::AccountId>, Self::Error> {
+		// an election already happened in this block, don't run it again against the already
+		// killed snapshot, but return the same result.
+		let now = <frame_system::Pallet<T>>::block_number();
+		if Self::last_election_block() == Some(now) {
+			if let Some(supports) = <ElectedSupports<T>>::get() {
+				log!(warn, "elect called more than once at block {:?}.", now);
+				return Ok(supports);
+			}
+		}
+
 		let outcome = Self::do_elect();
-		Self::post_elect();
+		match &outcome {
+			Ok(supports) => {
+				<ElectedSupports<T>>::put(supports);
+				// writing the supports now, and removing them in `on_finalize`.
+				<frame_system::Pallet<T>>::register_extra_weight_unchecked(
+					T::DbWeight::get().writes(2),
+					DispatchClass::Mandatory,
+				);
+				Self::post_elect();
+			}
+			Err(_) => {
+				// keep the snapshot and the round around, wait for `set_emergency_solution`.
+				log!(error, "election failed, entering emergency phase.");
+				<CurrentPhase<T>>::put(Phase::Emergency);
+			}
+		}
 		outcome
 	}
 }
@@ -1069,8 +2062,41 @@ mod feasibility_check {
 			let solution = raw_solution();
 
 			// for whatever reason it might be:
-			<Snapshot<Runtime>>::kill();
+			<SnapshotTargets<Runtime>>::kill();
+
+			assert_noop!(
+				MultiPhase::feasibility_check(solution, COMPUTE),
+				FeasibilityError::SnapshotUnavailable
+			);
+		})
+	}
+
+	#[test]
+	fn only_pages_of_the_solution_are_read() {
+		ExtBuilder::default().voter_snapshot_per_page(1).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			let solution = raw_solution();
+			let ready = MultiPhase::feasibility_check(solution.clone(), COMPUTE).unwrap();
+			let backers = ready
+				.supports
+				.iter()
+				.flat_map(|(_, support)| support.voters.iter().map(|(who, _)| *who))
+				.collect::<BTreeSet<_>>();
+
+			// the pages of all other voters are not needed.
+			let snapshot = MultiPhase::snapshot().unwrap();
+			let mut removed = 0;
+			for (page, (who, _, _)) in snapshot.voters.iter().enumerate() {
+				if !backers.contains(who) {
+					<SnapshotVoters<Runtime>>::remove(page as u32);
+					removed += 1;
+				}
+			}
+			assert!(removed > 0);
+			assert_eq!(MultiPhase::feasibility_check(solution.clone(), COMPUTE), Ok(ready));
 
+			// but those of the solution are.
+			<SnapshotVoters<Runtime>>::remove_all();
 			assert_noop!(
 				MultiPhase::feasibility_check(solution, COMPUTE),
 				FeasibilityError::SnapshotUnavailable
@@ -1095,15 +2121,39 @@ mod feasibility_check {
 
 	#[test]
 	fn desired_targets() {
-		ExtBuilder::default().desired_targets(8).build_and_execute(|| {
+		ExtBuilder::default().desired_targets(5).build_and_execute(|| {
+			// 50 has no backing, so it can never be a winner in the solution.
+			<Targets>::set(vec![10, 20, 30, 40, 50]);
 			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
 			assert!(MultiPhase::current_phase().is_signed());
 
 			let solution = raw_solution();
 
 			assert_eq!(solution.compact.unique_targets().len(), 4);
-			assert_eq!(MultiPhase::desired_targets().unwrap(), 8);
+			assert_eq!(MultiPhase::desired_targets().unwrap(), 5);
+
+			assert_noop!(
+				MultiPhase::feasibility_check(solution, COMPUTE),
+				FeasibilityError::WrongWinnerCount,
+			);
+		})
+	}
+
+	#[test]
+	fn desired_targets_is_read_from_snapshot() {
+		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let solution = raw_solution();
+			assert_eq!(solution.compact.unique_targets().len(), 2);
+			assert_eq!(MultiPhase::desired_targets().unwrap(), 2);
+			assert_ok!(MultiPhase::feasibility_check(solution.clone(), COMPUTE));
 
+			// the snapshot is the only source of the desired targets.
+			<SnapshotTargets<Runtime>>::mutate(|s| s.as_mut().unwrap().1 = 8);
+			assert_eq!(MultiPhase::desired_targets().unwrap(), 8);
+			assert_eq!(MultiPhase::snapshot().unwrap().desired_targets, 8);
 			assert_noop!(
 				MultiPhase::feasibility_check(solution, COMPUTE),
 				FeasibilityError::WrongWinnerCount,
@@ -1173,6 +2223,40 @@ mod feasibility_check {
 		})
 	}
 
+	#[test]
+	fn duplicate_voter() {
+		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let mut solution = raw_solution();
+			// list the first single-vote voter twice.
+			let first = solution.compact.votes1[0];
+			solution.compact.votes1.push(first);
+			assert_noop!(
+				MultiPhase::feasibility_check(solution, COMPUTE),
+				FeasibilityError::DuplicateVoter,
+			);
+		})
+	}
+
+	#[test]
+	fn duplicate_target_in_distribution() {
+		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let mut solution = raw_solution();
+			// make the first two-vote voter give both parts of its stake to the same target.
+			let (voter, (target, weight), _) = solution.compact.votes2[0];
+			solution.compact.votes2[0] = (voter, (target, weight), target);
+			assert_noop!(
+				MultiPhase::feasibility_check(solution, COMPUTE),
+				FeasibilityError::DuplicateTargetInDistribution,
+			);
+		})
+	}
+
 	#[test]
 	fn voter_votes() {
 		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
@@ -1202,6 +2286,118 @@ mod feasibility_check {
 		})
 	}
 
+	#[test]
+	fn check_solution_works() {
+		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let solution = raw_solution();
+			assert_eq!(MultiPhase::check_solution(solution.clone()), Ok(solution.score));
+			assert!(MultiPhase::queued_solution().is_none());
+
+			// point all votes from 3 to the non-existent 4.
+			let mut solution = raw_solution();
+			solution
+				.compact
+				.votes1
+				.iter_mut()
+				.filter(|(_, t)| *t == 3u16)
+				.for_each(|(_, t)| *t += 1);
+			solution.compact.votes2.iter_mut().for_each(|(_, (t0, _), t1)| {
+				if *t0 == 3u16 {
+					*t0 += 1
+				};
+				if *t1 == 3u16 {
+					*t1 += 1
+				};
+			});
+			let result = MultiPhase::check_solution(solution);
+			assert_eq!(result, Err(FeasibilityError::InvalidWinner));
+			assert!(MultiPhase::queued_solution().is_none());
+
+			// the outcome is returned as-is through the runtime API.
+			let encoded = result.encode();
+			let decoded = <Result<ElectionScore, FeasibilityError>>::decode(&mut &*encoded);
+			assert_eq!(decoded, Ok(result));
+		})
+	}
+
+	#[test]
+	fn near_boundary_distribution_is_not_over_staked() {
+		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let RoundSnapshot { voters, targets, .. } = MultiPhase::snapshot().unwrap();
+			let cache = helpers::generate_voter_cache::<Runtime>(&voters);
+			let voter_index = helpers::voter_index_fn_linear::<Runtime>(&voters);
+			let target_index = helpers::target_index_fn_linear::<Runtime>(&targets);
+			let voter_at = helpers::voter_at_fn::<Runtime>(&voters);
+			let target_at = helpers::target_at_fn::<Runtime>(&targets);
+			let stake_of = helpers::stake_of_fn::<Runtime>(&voters, &cache);
+
+			// voter 1 splits its stake into two parts that are just about to round up.
+			let assignments = vec![sp_npos_elections::Assignment {
+				who: 1,
+				distribution: vec![
+					(10, sp_runtime::PerU16::from_parts(u16::max_value() / 2 + 1)),
+					(20, sp_runtime::PerU16::from_parts(u16::max_value() / 2)),
+				],
+			}];
+			let compact =
+				<CompactOf<Runtime>>::from_assignment(assignments, &voter_index, &target_index)
+					.unwrap();
+			let score = compact.clone().score(&[10, 20], &stake_of, voter_at, target_at).unwrap();
+			let solution = RawSolution { compact, score, round: MultiPhase::round() };
+
+			let ready = MultiPhase::feasibility_check(solution, COMPUTE).unwrap();
+			let backing = ready
+				.supports
+				.iter()
+				.flat_map(|(_, support)| support.voters.iter())
+				.filter(|(who, _)| *who == 1)
+				.map(|(_, backing)| *backing)
+				.sum::<ExtendedBalance>();
+			assert_eq!(backing, 10);
+		})
+	}
+
+	#[test]
+	fn support_cap() {
+		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let solution = raw_solution();
+			// the total stake of the snapshot is 140.
+			let max_support = MultiPhase::feasibility_check(solution.clone(), COMPUTE)
+				.unwrap()
+				.supports
+				.iter()
+				.map(|(_, support)| support.total)
+				.max()
+				.unwrap();
+
+			// no cap.
+			assert_ok!(MultiPhase::feasibility_check(solution.clone(), COMPUTE));
+
+			// a cap that the best backed winner just fits in.
+			<MaxSupportPerTarget>::set(Some(Perbill::from_rational_approximation(max_support, 140)));
+			assert_ok!(MultiPhase::feasibility_check(solution.clone(), COMPUTE));
+
+			// a cap below that.
+			<MaxSupportPerTarget>::set(Some(Perbill::from_rational_approximation(
+				max_support - 1,
+				140,
+			)));
+			assert_noop!(
+				MultiPhase::feasibility_check(solution, COMPUTE),
+				FeasibilityError::SupportCapExceeded,
+			);
+		})
+	}
+
 	#[test]
 	fn score() {
 		ExtBuilder::default().desired_targets(2).build_and_execute(|| {
@@ -1224,9 +2420,10 @@ mod feasibility_check {
 
 #[cfg(test)]
 mod tests {
-	use super::{mock::*, Event, *};
+	use super::{mock::{Origin, *}, Event, *};
 	use sp_election_providers::ElectionProvider;
-	use sp_npos_elections::Support;
+	use sp_npos_elections::{ElectionResult, Support};
+	use sp_runtime::PerU16;
 
 	#[test]
 	fn phase_rotation_works() {
@@ -1362,72 +2559,1102 @@ mod tests {
 	}
 
 	#[test]
-	fn early_termination() {
-		// an early termination in the signed phase, with no queued solution.
+	fn huge_phases_do_not_overflow() {
+		ExtBuilder::default().phases(u64::MAX - 1, u64::MAX - 1).build_and_execute(|| {
+			// both phases are longer than the entire epoch: no signed phase, the unsigned phase
+			// opens right away.
+			roll_to(1);
+			assert_eq!(MultiPhase::current_phase(), Phase::Unsigned((true, 1)));
+			assert!(MultiPhase::snapshot().is_some());
+
+			roll_to(30);
+			assert_eq!(MultiPhase::current_phase(), Phase::Unsigned((true, 1)));
+		});
+
+		ExtBuilder::default().phases(u64::MAX, 5).build_and_execute(|| {
+			roll_to(1);
+			assert!(MultiPhase::current_phase().is_signed());
+
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+		});
+	}
+
+	#[test]
+	fn elect_with_bounds_checks_the_result() {
 		ExtBuilder::default().build_and_execute(|| {
-			// signed phase started at block 15 and will end at 25.
-			roll_to(14);
-			assert_eq!(MultiPhase::current_phase(), Phase::Off);
+			roll_to(25);
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			roll_to(30);
 
-			roll_to(15);
-			assert_eq!(multi_phase_events(), vec![Event::SignedPhaseStarted(1)]);
-			assert_eq!(MultiPhase::current_phase(), Phase::Signed);
-			assert_eq!(MultiPhase::round(), 1);
+			// the queued solution elects 2 winners, backed by 5 voters.
+			let supports = MultiPhase::queued_solution().unwrap().supports;
+			assert_eq!(supports.len(), 2);
+			assert_eq!(MultiPhase::elect_with_bounds(5, 2).unwrap(), supports);
+		});
 
-			// an unexpected call to elect.
-			roll_to(20);
-			MultiPhase::elect().unwrap();
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			roll_to(30);
 
-			// we surely can't have any feasible solutions. This will cause an on-chain election.
 			assert_eq!(
-				multi_phase_events(),
-				vec![
-					Event::SignedPhaseStarted(1),
-					Event::ElectionFinalized(Some(ElectionCompute::OnChain))
-				],
+				MultiPhase::elect_with_bounds(5, 1).unwrap_err(),
+				ElectionError::BoundsExceeded,
 			);
-			// all storage items must be cleared.
+		});
+
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			roll_to(30);
+
+			assert_eq!(
+				MultiPhase::elect_with_bounds(4, 2).unwrap_err(),
+				ElectionError::BoundsExceeded,
+			);
+		})
+	}
+
+	#[test]
+	fn elect_is_idempotent_within_block() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+
+			roll_to(30);
+			let supports = MultiPhase::elect().unwrap();
 			assert_eq!(MultiPhase::round(), 2);
 			assert!(MultiPhase::snapshot().is_none());
-			assert!(MultiPhase::snapshot_metadata().is_none());
-			assert!(MultiPhase::desired_targets().is_none());
-			assert!(MultiPhase::queued_solution().is_none());
+			assert_eq!(<ElectionsCompleted>::get(), 1);
+			let events = multi_phase_events().len();
+
+			// the second call neither falls back, nor runs any of the side effects again.
+			<Fallback>::set(FallbackStrategy::Nothing);
+			assert_eq!(MultiPhase::elect().unwrap(), supports);
+			assert_eq!(MultiPhase::round(), 2);
+			assert_eq!(MultiPhase::last_election_compute(), Some(ElectionCompute::Unsigned));
+			assert_eq!(<ElectionsCompleted>::get(), 1);
+			assert_eq!(multi_phase_events().len(), events);
+
+			// the cached result is gone once the block is finalized, and not used afterwards.
+			roll_to(31);
+			assert!(<ElectedSupports<Runtime>>::get().is_none());
+			<Fallback>::set(FallbackStrategy::OnChain);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::last_election_compute(), Some(ElectionCompute::OnChain));
+			assert_eq!(<ElectionsCompleted>::get(), 2);
+
+			// the result of that election is only kept within its block as well.
+			assert!(<ElectedSupports<Runtime>>::get().is_some());
+			roll_to(32);
+			assert!(<ElectedSupports<Runtime>>::get().is_none());
 		})
 	}
 
 	#[test]
-	fn fallback_strategy_works() {
-		ExtBuilder::default().fallabck(FallbackStrategy::OnChain).build_and_execute(|| {
-			roll_to(15);
-			assert_eq!(MultiPhase::current_phase(), Phase::Signed);
+	fn data_provider_is_notified_of_completed_election() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert_eq!(<ElectionsCompleted>::get(), 0);
+
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert_eq!(<ElectionsCompleted>::get(), 1);
+
+			// a failed election does not complete.
+			roll_to(55);
+			<Fallback>::set(FallbackStrategy::Nothing);
+			assert_eq!(MultiPhase::elect().unwrap_err(), ElectionError::NoFallbackConfigured);
+			assert_eq!(<ElectionsCompleted>::get(), 1);
+		})
+	}
+
+	#[test]
+	fn last_election_compute_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert!(MultiPhase::last_election_compute().is_none());
 
+			// round 1: an unsigned solution is queued.
 			roll_to(25);
-			assert_eq!(MultiPhase::current_phase(), Phase::Unsigned((true, 25)));
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::last_election_compute(), Some(ElectionCompute::Unsigned));
 
-			// zilch solutions thus far.
-			let supports = MultiPhase::elect().unwrap();
+			// round 2: nothing is queued, the on-chain fallback is used.
+			roll_to(60);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::last_election_compute(), Some(ElectionCompute::OnChain));
+
+			// round 3: a failed election does not change it.
+			roll_to(90);
+			<Fallback>::set(FallbackStrategy::Nothing);
+			assert!(MultiPhase::elect().is_err());
+			assert_eq!(MultiPhase::last_election_compute(), Some(ElectionCompute::OnChain));
+		})
+	}
+
+	#[test]
+	fn stale_queued_solution_is_not_enacted() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert_eq!(MultiPhase::round(), 1);
 
+			// a solution of a previous round is left in storage.
+			let stale = ReadySolution {
+				supports: vec![(30, Support { total: 40, voters: vec![(30, 40)] })],
+				score: [40, 40, 1600],
+				compute: ElectionCompute::Unsigned,
+				round: 0,
+			};
+			<QueuedSolution<Runtime>>::put(stale.clone());
+
+			// the fallback is used instead.
+			let supports = MultiPhase::elect().unwrap();
+			assert_ne!(supports, stale.supports);
 			assert_eq!(
-				supports,
-				vec![
-					(30, Support { total: 40, voters: vec![(2, 5), (4, 5), (30, 30)] }),
-					(40, Support { total: 60, voters: vec![(2, 5), (3, 10), (4, 5), (40, 40)] })
-				]
-			)
-		});
+				multi_phase_events().last(),
+				Some(&Event::ElectionFinalized(Some(ElectionCompute::OnChain))),
+			);
+			assert!(MultiPhase::queued_solution().is_none());
+		})
+	}
 
-		ExtBuilder::default().fallabck(FallbackStrategy::Nothing).build_and_execute(|| {
+	#[test]
+	fn post_election_cooldown_works() {
+		ExtBuilder::default().post_election_cooldown(20).build_and_execute(|| {
 			roll_to(15);
-			assert_eq!(MultiPhase::current_phase(), Phase::Signed);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert!(MultiPhase::last_election_block().is_none());
 
-			roll_to(25);
-			assert_eq!(MultiPhase::current_phase(), Phase::Unsigned((true, 25)));
+			roll_to(32);
+			MultiPhase::elect().unwrap();
+			assert!(MultiPhase::current_phase().is_off());
+			assert_eq!(MultiPhase::last_election_block(), Some(32));
+
+			// the signed phase would normally start at 45, but we are cooling down until 52.
+			roll_to(45);
+			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::snapshot().is_none());
+
+			roll_to(51);
+			assert!(MultiPhase::current_phase().is_off());
+
+			roll_to(52);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert!(MultiPhase::snapshot().is_some());
+
+			roll_to(55);
+			assert!(MultiPhase::current_phase().is_unsigned_open_at(55));
+		})
+	}
+
+	#[test]
+	fn phase_start_blocks_are_recorded() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_eq!(MultiPhase::phase_starts(1), PhaseStarts::default());
+
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_eq!(MultiPhase::phase_starts(1), PhaseStarts { signed: Some(15), unsigned: None });
+
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert_eq!(
+				MultiPhase::phase_starts(1),
+				PhaseStarts { signed: Some(15), unsigned: Some(25) },
+			);
+
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+
+			roll_to(55);
+			assert_eq!(MultiPhase::round(), 2);
+			assert_eq!(
+				MultiPhase::phase_starts(2),
+				PhaseStarts { signed: Some(45), unsigned: Some(55) },
+			);
+			// the previous round is kept untouched.
+			assert_eq!(
+				MultiPhase::phase_starts(1),
+				PhaseStarts { signed: Some(15), unsigned: Some(25) },
+			);
+		})
+	}
+
+	#[test]
+	fn paged_snapshot_works() {
+		ExtBuilder::default().voter_snapshot_per_page(3).build_and_execute(|| {
+			roll_to(15);
+			assert!(MultiPhase::current_phase().is_signed());
+
+			// 8 voters in pages of 3.
+			assert_eq!(MultiPhase::snapshot_pages(), Some(3));
+			assert_eq!(<SnapshotVoters<Runtime>>::get(0).unwrap().0.len(), 3);
+			assert_eq!(<SnapshotVoters<Runtime>>::get(1).unwrap().0.len(), 3);
+			assert_eq!(<SnapshotVoters<Runtime>>::get(2).unwrap().0.len(), 2);
+			assert!(<SnapshotVoters<Runtime>>::get(3).is_none());
+
+			// the pages are assembled back into the full snapshot, in order.
+			let mut voters = Voters::get();
+			voters.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+			assert_eq!(
+				MultiPhase::snapshot().unwrap(),
+				RoundSnapshot { voters, targets: Targets::get(), desired_targets: 2 },
+			);
+
+			// a full election over the paged snapshot.
+			roll_to(25);
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			let supports = MultiPhase::elect().unwrap();
+			assert_eq!(supports.len(), 2);
+			assert_eq!(MultiPhase::last_election_compute(), Some(ElectionCompute::Unsigned));
+
+			// all of the pages are cleared.
+			assert!(MultiPhase::snapshot().is_none());
+			assert!(MultiPhase::snapshot_pages().is_none());
+			assert_eq!(<SnapshotVoters<Runtime>>::iter().count(), 0);
+		})
+	}
+
+	#[test]
+	fn total_vote_weight_works() {
+		let snapshot = RoundSnapshot::<u64> {
+			voters: vec![(1, 10, vec![10]), (2, 20, vec![10, 20]), (3, 5, vec![])],
+			targets: vec![10, 20],
+			desired_targets: 1,
+		};
+		assert_eq!(snapshot.total_vote_weight(), 35);
+		assert_eq!(RoundSnapshot::<u64>::default().total_vote_weight(), 0);
+
+		// does not overflow the vote weight.
+		let snapshot = RoundSnapshot::<u64> {
+			voters: vec![(1, VoteWeight::max_value(), vec![]), (2, VoteWeight::max_value(), vec![])],
+			..Default::default()
+		};
+		assert_eq!(snapshot.total_vote_weight(), 2 * VoteWeight::max_value() as ExtendedBalance);
+
+		// matches the data provider in the mock.
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(15);
+			let manual: ExtendedBalance =
+				Voters::get().iter().map(|(_, w, _)| *w as ExtendedBalance).sum();
+			assert_eq!(MultiPhase::snapshot().unwrap().total_vote_weight(), manual);
+		})
+	}
+
+	#[test]
+	fn paged_snapshot_reads_work() {
+		ExtBuilder::default().voter_snapshot_per_page(3).build_and_execute(|| {
+			// no snapshot yet.
+			assert!(MultiPhase::snapshot_voters_paged(0, 2).is_empty());
+			assert!(MultiPhase::snapshot_targets_paged(0, 2).is_empty());
+
+			roll_to(15);
+			let snapshot = MultiPhase::snapshot().unwrap();
+
+			// any page size reassembles into the full snapshot, regardless of the storage pages.
+			for page_size in 1..=10 {
+				let mut voters = vec![];
+				let mut page = 0;
+				loop {
+					let read = MultiPhase::snapshot_voters_paged(page, page_size);
+					if read.is_empty() {
+						break
+					}
+					assert!(read.len() <= page_size as usize);
+					voters.extend(read);
+					page += 1;
+				}
+				assert_eq!(voters, snapshot.voters);
+
+				let targets = (0..)
+					.map(|page| MultiPhase::snapshot_targets_paged(page, page_size))
+					.take_while(|read| !read.is_empty())
+					.flatten()
+					.collect::<Vec<_>>();
+				assert_eq!(targets, snapshot.targets);
+			}
+
+			// a page within the second and third storage page.
+			assert_eq!(MultiPhase::snapshot_voters_paged(1, 4), snapshot.voters[4..8].to_vec());
+			// past the end, or of size zero.
+			assert!(MultiPhase::snapshot_voters_paged(2, 4).is_empty());
+			assert!(MultiPhase::snapshot_voters_paged(u32::MAX, u32::MAX).is_empty());
+			assert!(MultiPhase::snapshot_voters_paged(0, 0).is_empty());
+			assert!(MultiPhase::snapshot_targets_paged(u32::MAX, u32::MAX).is_empty());
+
+			// only the overlapping storage pages are read.
+			<SnapshotVoters<Runtime>>::remove(0);
+			assert_eq!(MultiPhase::snapshot_voters_paged(1, 4), snapshot.voters[4..8].to_vec());
+		})
+	}
+
+	#[test]
+	fn voter_page_decoding_is_bounded() {
+		ExtBuilder::default().voter_snapshot_per_page(3).build_and_execute(|| {
+			let voter = (1u64, 10u64, vec![10u64, 20]);
+
+			// a full page is fine.
+			let full = vec![voter.clone(); 3].encode();
+			assert_eq!(VoterPage::<Runtime>::decode(&mut &*full).unwrap().0.len(), 3);
+
+			// one more voter than allowed.
+			let over = vec![voter.clone(); 4].encode();
+			assert!(VoterPage::<Runtime>::decode(&mut &*over).is_err());
+
+			// a bogus, huge length prefix is rejected before anything is allocated.
+			let mut bogus = codec::Compact(u32::max_value()).encode();
+			bogus.extend(voter.encode());
+			assert!(VoterPage::<Runtime>::decode(&mut &*bogus).is_err());
+
+			// so is a voter with more votes than a compact solution can hold.
+			let votes = (1u64, 10u64, (0..17u64).collect::<Vec<_>>());
+			assert!(VoterPage::<Runtime>::decode(&mut &*vec![votes].encode()).is_err());
+
+			// and more targets than a compact solution can index.
+			let targets = (0..=u16::max_value() as u64).collect::<Vec<_>>();
+			assert!(TargetList::<Runtime>::decode(&mut &*targets.encode()).is_ok());
+			let targets = (0..=u16::max_value() as u64 + 1).collect::<Vec<_>>();
+			assert!(TargetList::<Runtime>::decode(&mut &*targets.encode()).is_err());
+
+			// a corrupt page in storage makes the snapshot unavailable, rather than panicking.
+			roll_to(15);
+			assert!(MultiPhase::snapshot().is_some());
+			sp_io::storage::set(&<SnapshotVoters<Runtime>>::hashed_key_for(0), &bogus);
+			assert!(MultiPhase::snapshot().is_none());
+		})
+	}
+
+	#[test]
+	fn snapshot_drops_what_solutions_cannot_hold() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_eq!(MultiPhase::max_snapshot_targets(), 1 << 16);
+			assert_eq!(MultiPhase::max_votes_per_voter(), 16);
+
+			MultiPhase::put_snapshot(RoundSnapshot {
+				voters: vec![(1, 10, (0..20).collect()), (2, 10, vec![0, 1])],
+				targets: (0..(1 << 16) + 10).collect(),
+				desired_targets: 2,
+			});
+
+			let snapshot = MultiPhase::snapshot().unwrap();
+			assert_eq!(snapshot.targets, (0..1 << 16).collect::<Vec<_>>());
+			assert_eq!(snapshot.voters[0].2, (0..16).collect::<Vec<_>>());
+			assert_eq!(snapshot.voters[1].2, vec![0, 1]);
+			assert_eq!(
+				MultiPhase::snapshot_metadata(),
+				Some(SolutionOrSnapshotSize { voters: 2, targets: 1 << 16 }),
+			);
+		})
+	}
+
+	#[test]
+	fn runtime_upgrade_kills_snapshot_of_other_page_size() {
+		ExtBuilder::default().voter_snapshot_per_page(3).build_and_execute(|| {
+			roll_to(15);
+			assert_eq!(MultiPhase::snapshot_page_size(), Some(3));
+
+			// same page size, nothing to do.
+			<MultiPhase as frame_support::traits::OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert!(MultiPhase::snapshot().is_some());
+
+			// the pages of 3 voters would not decode anymore.
+			<VoterSnapshotPerPage>::set(2);
+			assert!(MultiPhase::snapshot().is_none());
+			<MultiPhase as frame_support::traits::OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert!(MultiPhase::snapshot_pages().is_none());
+			assert!(MultiPhase::snapshot_page_size().is_none());
+			assert!(MultiPhase::snapshot_metadata().is_none());
+			assert_eq!(<SnapshotVoters<Runtime>>::iter().count(), 0);
+
+			// the round still ends with the fallback.
+			roll_to(30);
+			assert!(MultiPhase::elect().is_ok());
+			assert_eq!(MultiPhase::last_election_compute(), Some(ElectionCompute::OnChain));
+
+			// and the next snapshot uses the new page size.
+			roll_to(45);
+			assert_eq!(MultiPhase::snapshot_page_size(), Some(2));
+			assert_eq!(MultiPhase::snapshot_pages(), Some(4));
+			assert!(MultiPhase::snapshot().is_some());
+		})
+	}
+
+	#[test]
+	fn early_termination() {
+		// an early termination in the signed phase, with no queued solution.
+		ExtBuilder::default().build_and_execute(|| {
+			// signed phase started at block 15 and will end at 25.
+			roll_to(14);
+			assert_eq!(MultiPhase::current_phase(), Phase::Off);
+
+			roll_to(15);
+			assert_eq!(multi_phase_events(), vec![Event::SignedPhaseStarted(1)]);
+			assert_eq!(MultiPhase::current_phase(), Phase::Signed);
+			assert_eq!(MultiPhase::round(), 1);
+
+			// an unexpected call to elect.
+			roll_to(20);
+			MultiPhase::elect().unwrap();
+
+			// we surely can't have any feasible solutions. This will cause an on-chain election.
+			assert_eq!(
+				multi_phase_events(),
+				vec![
+					Event::SignedPhaseStarted(1),
+					Event::ElectionFinalized(Some(ElectionCompute::OnChain))
+				],
+			);
+			// all storage items must be cleared.
+			assert_eq!(MultiPhase::round(), 2);
+			assert!(MultiPhase::snapshot().is_none());
+			assert!(MultiPhase::snapshot_metadata().is_none());
+			assert!(MultiPhase::desired_targets().is_none());
+			assert!(MultiPhase::queued_solution().is_none());
+		})
+	}
+
+	#[test]
+	fn enacted_scores_are_recorded_and_pruned() {
+		ExtBuilder::default().history_depth(2).build_and_execute(|| {
+			let mut scores = vec![];
+			for round in 1..=3 {
+				roll_to(round * 30 + 2);
+				assert!(MultiPhase::current_phase().is_unsigned());
+				assert_eq!(MultiPhase::round(), round as u32);
+
+				let supports = MultiPhase::elect().unwrap();
+				scores.push((&supports).evaluate());
+				assert_eq!(
+					MultiPhase::enacted_scores(round as u32),
+					Some((scores[round as usize - 1], ElectionCompute::OnChain)),
+				);
+			}
+
+			// round 1 has been pruned.
+			assert!(MultiPhase::enacted_scores(1).is_none());
+			assert_eq!(MultiPhase::enacted_scores(2), Some((scores[1], ElectionCompute::OnChain)));
+			assert_eq!(MultiPhase::enacted_scores(3), Some((scores[2], ElectionCompute::OnChain)));
+			assert_eq!(<EnactedScores<Runtime>>::iter().count(), 2);
+		})
+	}
+
+	#[test]
+	fn fallback_strategy_works() {
+		ExtBuilder::default().fallabck(FallbackStrategy::OnChain).build_and_execute(|| {
+			roll_to(15);
+			assert_eq!(MultiPhase::current_phase(), Phase::Signed);
+
+			roll_to(25);
+			assert_eq!(MultiPhase::current_phase(), Phase::Unsigned((true, 25)));
+
+			// zilch solutions thus far.
+			let supports = MultiPhase::elect().unwrap();
+
+			assert_eq!(
+				supports,
+				vec![
+					(30, Support { total: 40, voters: vec![(2, 5), (4, 5), (30, 30)] }),
+					(40, Support { total: 60, voters: vec![(2, 5), (3, 10), (4, 5), (40, 40)] })
+				]
+			)
+		});
+
+		ExtBuilder::default().fallabck(FallbackStrategy::Nothing).build_and_execute(|| {
+			roll_to(15);
+			assert_eq!(MultiPhase::current_phase(), Phase::Signed);
+
+			roll_to(25);
+			assert_eq!(MultiPhase::current_phase(), Phase::Unsigned((true, 25)));
 
 			// zilch solutions thus far.
 			assert_eq!(MultiPhase::elect().unwrap_err(), ElectionError::NoFallbackConfigured);
 		})
 	}
 
+	#[test]
+	fn check_witness_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// no snapshot yet.
+			assert_noop!(
+				MultiPhase::check_witness(&witness()),
+				Error::<Runtime>::WitnessSnapshotUnavailable,
+			);
+
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// correct witness.
+			assert_ok!(MultiPhase::check_witness(&witness()));
+
+			// wrong voter count.
+			let mut wrong = witness();
+			wrong.voters += 1;
+			assert_noop!(MultiPhase::check_witness(&wrong), Error::<Runtime>::WitnessWrongVoterCount);
+
+			// wrong target count.
+			let mut wrong = witness();
+			wrong.targets -= 1;
+			assert_noop!(
+				MultiPhase::check_witness(&wrong),
+				Error::<Runtime>::WitnessWrongTargetCount,
+			);
+		})
+	}
+
+	#[test]
+	fn check_compact_witness_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::check_compact_witness(&solution.compact, &witness));
+
+			// more voters than the witness claims.
+			let wrong = SolutionOrSnapshotSize {
+				voters: solution.compact.voter_count() as u32 - 1,
+				..witness
+			};
+			assert_eq!(
+				MultiPhase::check_compact_witness(&solution.compact, &wrong),
+				Err(FeasibilityError::WitnessCompactMismatch),
+			);
+
+			// more targets than the witness claims.
+			let wrong = SolutionOrSnapshotSize {
+				targets: solution.compact.unique_targets().len() as u32 - 1,
+				..witness
+			};
+			assert_eq!(
+				MultiPhase::check_compact_witness(&solution.compact, &wrong),
+				Err(FeasibilityError::WitnessCompactMismatch),
+			);
+		})
+	}
+
+	#[test]
+	fn queued_support_of_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// nothing queued yet.
+			assert!(MultiPhase::queued_support_of(&40).is_none());
+
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			let supports = MultiPhase::queued_solution().unwrap().supports;
+			assert_eq!(supports.iter().map(|(w, _)| *w).collect::<Vec<_>>(), vec![30, 40]);
+
+			// a winner.
+			let (total, backers) = MultiPhase::queued_support_of(&40).unwrap();
+			assert_eq!(total, supports[1].1.total);
+			assert_eq!(backers, supports[1].1.voters);
+			assert_eq!(total, backers.iter().map(|(_, s)| s).sum::<ExtendedBalance>());
+
+			// not a winner.
+			assert!(MultiPhase::queued_support_of(&10).is_none());
+		})
+	}
+
+	#[test]
+	fn score_improvement_over_queued_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// nothing queued yet.
+			assert!(MultiPhase::score_improvement_over_queued([110, 0, 0]).is_none());
+
+			let ready = ReadySolution { score: [100, 50, 1000], ..Default::default() };
+			<QueuedSolution<Runtime>>::put(ready);
+
+			assert_eq!(
+				MultiPhase::score_improvement_over_queued([110, 0, 0]),
+				Some(Perbill::from_percent(10)),
+			);
+			assert_eq!(
+				MultiPhase::score_improvement_over_queued([125, 60, 900]),
+				Some(Perbill::from_percent(25)),
+			);
+
+			// not an improvement.
+			assert_eq!(
+				MultiPhase::score_improvement_over_queued([100, 60, 900]),
+				Some(Perbill::zero()),
+			);
+			assert_eq!(MultiPhase::score_improvement_over_queued([90, 0, 0]), Some(Perbill::zero()));
+
+			// saturated.
+			assert_eq!(MultiPhase::score_improvement_over_queued([300, 0, 0]), Some(Perbill::one()));
+		})
+	}
+
+	#[test]
+	fn blocks_until_next_election_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(1);
+			assert_eq!(MultiPhase::blocks_until_next_election(), 29);
+
+			roll_to(15);
+			assert_eq!(MultiPhase::blocks_until_next_election(), 15);
+
+			roll_to(29);
+			assert_eq!(MultiPhase::blocks_until_next_election(), 1);
+
+			// the prediction moves on to the next election.
+			roll_to(30);
+			assert_eq!(MultiPhase::blocks_until_next_election(), 30);
+		})
+	}
+
+	#[test]
+	fn export_round_state_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+
+			let export = MultiPhase::export_round_state();
+			let decoded =
+				<RoundStateExport<AccountId, u64> as Decode>::decode(&mut &*export.encode())
+					.unwrap();
+			assert_eq!(decoded, export);
+
+			assert_eq!(export.round, MultiPhase::round());
+			assert_eq!(export.phase, Phase::Unsigned((true, 25)));
+			assert_eq!(export.snapshot, MultiPhase::snapshot());
+			assert_eq!(export.snapshot_metadata, Some(witness));
+			assert_eq!(export.desired_targets, Some(2));
+			assert!(export.queued_solution.is_some());
+			assert_eq!(export.queued_solution, MultiPhase::queued_solution());
+		})
+	}
+
+	#[test]
+	fn failed_elect_enters_emergency_phase() {
+		ExtBuilder::default().fallabck(FallbackStrategy::Nothing).build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			assert_eq!(MultiPhase::elect().unwrap_err(), ElectionError::NoFallbackConfigured);
+			assert!(MultiPhase::current_phase().is_emergency());
+			assert_eq!(multi_phase_events().last(), Some(&Event::ElectionFinalized(None)));
+
+			// round data is kept, and no new phase starts.
+			assert_eq!(MultiPhase::round(), 1);
+			assert!(MultiPhase::snapshot().is_some());
+			roll_to(60);
+			assert!(MultiPhase::current_phase().is_emergency());
+
+			// still failing, still emergency.
+			assert!(MultiPhase::elect().is_err());
+			assert!(MultiPhase::current_phase().is_emergency());
+		})
+	}
+
+	#[test]
+	fn set_emergency_solution_works() {
+		ExtBuilder::default().fallabck(FallbackStrategy::Nothing).build_and_execute(|| {
+			let supports = vec![(30, Support { total: 40, voters: vec![(30, 40)] })];
+
+			// not in emergency.
+			assert_noop!(
+				MultiPhase::set_emergency_solution(Origin::root(), supports.clone()),
+				Error::<Runtime>::CallNotAllowed,
+			);
+
+			roll_to(25);
+			assert!(MultiPhase::elect().is_err());
+			assert!(MultiPhase::current_phase().is_emergency());
+
+			// only the force origin.
+			assert_noop!(
+				MultiPhase::set_emergency_solution(Origin::signed(99), supports.clone()),
+				DispatchError::BadOrigin,
+			);
+
+			assert_ok!(MultiPhase::set_emergency_solution(Origin::root(), supports.clone()));
+			assert_eq!(
+				multi_phase_events().last(),
+				Some(&Event::SolutionStored(ElectionCompute::Emergency)),
+			);
+			assert_eq!(
+				MultiPhase::queued_solution().unwrap().compute,
+				ElectionCompute::Emergency
+			);
+
+			// next elect returns the given supports and ends the emergency.
+			assert_eq!(MultiPhase::elect().unwrap(), supports);
+			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::snapshot().is_none());
+			assert_eq!(MultiPhase::round(), 2);
+		})
+	}
+
+	#[test]
+	fn emergency_phase_is_left_after_emergency_solution() {
+		ExtBuilder::default().fallabck(FallbackStrategy::Nothing).build_and_execute(|| {
+			roll_to(30);
+			assert!(MultiPhase::elect().is_err());
+
+			// no new phase is opened while in emergency.
+			roll_to(50);
+			assert!(MultiPhase::current_phase().is_emergency());
+			assert_eq!(MultiPhase::round(), 1);
+
+			let supports = vec![(30, Support { total: 40, voters: vec![(30, 40)] })];
+			assert_ok!(MultiPhase::set_emergency_solution(Origin::root(), supports.clone()));
+			assert_eq!(MultiPhase::elect().unwrap(), supports);
+			assert!(MultiPhase::current_phase().is_off());
+
+			// the next round runs as usual, within what is left of the current epoch.
+			roll_to(51);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert_eq!(MultiPhase::round(), 2);
+
+			roll_to(55);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+
+			roll_to(60);
+			assert_eq!(MultiPhase::elect().unwrap().len(), 2);
+			assert!(MultiPhase::current_phase().is_off());
+			assert_eq!(MultiPhase::last_election_compute(), Some(ElectionCompute::Unsigned));
+		})
+	}
+
+	#[test]
+	fn set_unsigned_solution_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+
+			// only for the current round.
+			let mut stale = solution.clone();
+			stale.round -= 1;
+			assert_noop!(
+				MultiPhase::set_unsigned_solution(Origin::root(), stale, witness),
+				Error::<Runtime>::PreDispatchWrongRound,
+			);
+
+			// only the force origin.
+			assert_noop!(
+				MultiPhase::set_unsigned_solution(Origin::signed(99), solution.clone(), witness),
+				DispatchError::BadOrigin,
+			);
+
+			// a wrong witness is an error.
+			let mut wrong = witness;
+			wrong.voters += 1;
+			assert_noop!(
+				MultiPhase::set_unsigned_solution(Origin::root(), solution.clone(), wrong),
+				Error::<Runtime>::WitnessWrongVoterCount,
+			);
+
+			// an infeasible solution is an error, not a panic.
+			let mut invalid = solution.clone();
+			invalid.score[0] += 1;
+			assert_noop!(
+				MultiPhase::set_unsigned_solution(Origin::root(), invalid, witness),
+				Error::<Runtime>::InvalidSolution,
+			);
+
+			assert_ok!(MultiPhase::set_unsigned_solution(Origin::root(), solution.clone(), witness));
+			assert_eq!(
+				multi_phase_events().last(),
+				Some(&Event::SolutionStored(ElectionCompute::Governance)),
+			);
+			let queued = MultiPhase::queued_solution().unwrap();
+			assert_eq!(queued.score, solution.score);
+			assert_eq!(queued.compute, ElectionCompute::Governance);
+
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert_eq!(MultiPhase::last_election_compute(), Some(ElectionCompute::Governance));
+			assert_eq!(
+				MultiPhase::enacted_scores(1),
+				Some((solution.score, ElectionCompute::Governance)),
+			);
+		})
+	}
+
+	#[test]
+	fn set_unsigned_solution_replaces_better_queued_solution() {
+		ExtBuilder::default()
+			.desired_targets(1)
+			.add_voter(7, 2, vec![10])
+			.add_voter(8, 5, vec![10])
+			.build_and_execute(|| {
+				roll_to(25);
+				let backed_by = |voters: Vec<u64>| ElectionResult {
+					winners: vec![(10, 0)],
+					assignments: voters
+						.into_iter()
+						.map(|who| Assignment { who, distribution: vec![(10, PerU16::one())] })
+						.collect(),
+				};
+
+				let (best, witness) =
+					MultiPhase::prepare_election_result(backed_by(vec![10, 7, 8])).unwrap();
+				assert_ok!(MultiPhase::submit_unsigned(Origin::none(), best, witness));
+				assert_eq!(MultiPhase::queued_solution().unwrap().score[0], 17);
+
+				// a worse solution still replaces the queued one, by intent.
+				let (worse, witness) =
+					MultiPhase::prepare_election_result(backed_by(vec![10])).unwrap();
+				assert_eq!(worse.score[0], 10);
+				assert_ok!(MultiPhase::set_unsigned_solution(Origin::root(), worse, witness));
+				let queued = MultiPhase::queued_solution().unwrap();
+				assert_eq!(queued.score[0], 10);
+				assert_eq!(queued.compute, ElectionCompute::Governance);
+			})
+	}
+
+	#[test]
+	fn set_unsigned_solution_is_not_allowed_in_phase_off() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert!(MultiPhase::current_phase().is_off());
+			assert_noop!(
+				MultiPhase::set_unsigned_solution(Origin::root(), solution, witness),
+				Error::<Runtime>::CallNotAllowed,
+			);
+		})
+	}
+
+	#[test]
+	fn set_phase_overrides_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(20);
+			assert!(MultiPhase::current_phase().is_signed());
+
+			assert_noop!(
+				MultiPhase::set_phase_overrides(Origin::signed(99), Some(5), Some(3)),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(MultiPhase::set_phase_overrides(Origin::root(), Some(5), Some(3)));
+			assert_eq!(MultiPhase::pending_phase_overrides(), Some((Some(5), Some(3))));
+
+			// the ongoing round is not disrupted.
+			assert_eq!(MultiPhase::signed_phase_length(), 10);
+			assert_eq!(MultiPhase::unsigned_phase_length(), 5);
+			roll_to(24);
+			assert!(MultiPhase::current_phase().is_signed());
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned_open_at(25));
+
+			roll_to(30);
+			assert_ok!(MultiPhase::elect());
+			assert!(MultiPhase::pending_phase_overrides().is_none());
+			assert_eq!(MultiPhase::signed_phase_length(), 5);
+			assert_eq!(MultiPhase::unsigned_phase_length(), 3);
+
+			// the next round uses the new lengths: the election is at 60.
+			roll_to(51);
+			assert!(MultiPhase::current_phase().is_off());
+			roll_to(52);
+			assert!(MultiPhase::current_phase().is_signed());
+			roll_to(56);
+			assert!(MultiPhase::current_phase().is_signed());
+			roll_to(57);
+			assert!(MultiPhase::current_phase().is_unsigned_open_at(57));
+
+			// and can be reverted, again at the end of the round.
+			assert_ok!(MultiPhase::set_phase_overrides(Origin::root(), None, None));
+			assert_eq!(MultiPhase::unsigned_phase_length(), 3);
+			roll_to(60);
+			assert_ok!(MultiPhase::elect());
+			assert_eq!(MultiPhase::signed_phase_length(), 10);
+			assert_eq!(MultiPhase::unsigned_phase_length(), 5);
+		})
+	}
+
+	#[test]
+	fn miner_max_weight_of_block_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			let weights = <Runtime as frame_system::Config>::BlockWeights::get();
+			let overhead = weights.base_block + weights.get(DispatchClass::Normal).base_extrinsic;
+			assert_eq!(
+				MultiPhase::miner_max_weight_of_block(Perbill::one()),
+				weights.max_block - overhead,
+			);
+			assert_eq!(
+				MultiPhase::miner_max_weight_of_block(Perbill::from_percent(50)),
+				weights.max_block / 2 - overhead,
+			);
+			assert_eq!(MultiPhase::miner_max_weight_of_block(Perbill::zero()), 0);
+
+			// the mock uses the entire block.
+			<MultiPhase as frame_support::traits::IntegrityTest>::integrity_test();
+		})
+	}
+
+	#[test]
+	#[should_panic(expected = "MinerMaxWeight must not exceed the maximum block weight")]
+	fn integrity_test_rejects_miner_weight_above_block_weight() {
+		let max_block = <Runtime as frame_system::Config>::BlockWeights::get().max_block;
+		ExtBuilder::default().miner_weight(max_block + 1).build_and_execute(|| {
+			<MultiPhase as frame_support::traits::IntegrityTest>::integrity_test();
+		})
+	}
+
+	#[test]
+	#[should_panic(expected = "VoterSnapshotPerPage must not be zero")]
+	fn integrity_test_rejects_empty_snapshot_pages() {
+		ExtBuilder::default().voter_snapshot_per_page(0).build_and_execute(|| {
+			<MultiPhase as frame_support::traits::IntegrityTest>::integrity_test();
+		})
+	}
+
+	#[test]
+	fn clear_round_data_is_not_allowed_in_phase_off() {
+		ExtBuilder::default().build_and_execute(|| {
+			// nothing to clear.
+			roll_to(5);
+			assert!(MultiPhase::current_phase().is_off());
+			assert_noop!(
+				MultiPhase::clear_round_data(Origin::root(), 0),
+				Error::<Runtime>::CallNotAllowed,
+			);
+
+			// neither after an election.
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+			assert!(MultiPhase::current_phase().is_off());
+			assert_noop!(
+				MultiPhase::clear_round_data(Origin::root(), 0),
+				Error::<Runtime>::CallNotAllowed,
+			);
+		})
+	}
+
+	#[test]
+	fn clear_round_data_works_outside_phase_off() {
+		ExtBuilder::default().voter_snapshot_per_page(3).build_and_execute(|| {
+			// `elect` is never called, the round is stuck in the unsigned phase.
+			roll_to(25);
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			roll_to(40);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert_eq!(MultiPhase::snapshot_pages(), Some(3));
+
+			assert_noop!(
+				MultiPhase::clear_round_data(Origin::signed(99), 3),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				MultiPhase::clear_round_data(Origin::root(), 2),
+				Error::<Runtime>::WitnessWrongPageCount,
+			);
+			assert_ok!(MultiPhase::clear_round_data(Origin::root(), 3));
+			assert!((0..3).all(|page| !<SnapshotVoters<Runtime>>::contains_key(page)));
+
+			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::snapshot().is_none());
+			assert!(MultiPhase::snapshot_metadata().is_none());
+			assert!(MultiPhase::desired_targets().is_none());
+			assert!(MultiPhase::queued_solution().is_none());
+			assert!(MultiPhase::queued_solution_hash().is_none());
+			assert_eq!(MultiPhase::round(), 1);
+
+			// the round is opened afresh.
+			roll_to(45);
+			assert!(MultiPhase::current_phase().is_signed());
+			assert!(MultiPhase::snapshot().is_some());
+			assert_eq!(MultiPhase::round(), 1);
+		})
+	}
+
+	#[test]
+	fn clear_round_data_leaves_emergency() {
+		ExtBuilder::default().fallabck(FallbackStrategy::Nothing).build_and_execute(|| {
+			roll_to(30);
+			assert!(MultiPhase::elect().is_err());
+			assert!(MultiPhase::current_phase().is_emergency());
+
+			assert_ok!(MultiPhase::clear_round_data(Origin::root(), 1));
+			assert!(MultiPhase::current_phase().is_off());
+			assert!(MultiPhase::snapshot().is_none());
+
+			roll_to(45);
+			assert!(MultiPhase::current_phase().is_signed());
+		})
+	}
+
+	#[test]
+	fn error_display_works() {
+		use unsigned::MinerError;
+
+		let npos = || sp_npos_elections::Error::CompactInvalidIndex;
+		let npos_message = format!("npos elections error: {:?}", npos());
+		assert_eq!(
+			ElectionError::Feasibility(FeasibilityError::InvalidScore).to_string(),
+			"feasibility error: invalid score",
+		);
+		assert_eq!(
+			ElectionError::Miner(MinerError::PoolSubmissionFailed).to_string(),
+			"miner error: submitting to the pool failed",
+		);
+		assert_eq!(
+			ElectionError::Miner(MinerError::SnapshotUnAvailable).to_string(),
+			"miner error: snapshot unavailable",
+		);
+		assert_eq!(
+			ElectionError::OnChainFallback(onchain::Error::NposElections(npos())).to_string(),
+			format!("on-chain fallback error: {}", npos_message),
+		);
+		assert_eq!(ElectionError::NoFallbackConfigured.to_string(), "no fallback configured");
+		assert_eq!(ElectionError::BoundsExceeded.to_string(), "election bounds exceeded");
+
+		assert_eq!(MinerError::NposElections(npos()).to_string(), npos_message);
+		assert_eq!(MinerError::Solver("oops").to_string(), "solver error: oops");
+		assert_eq!(
+			MinerError::PreDispatchChecksFailed.to_string(),
+			"pre-dispatch checks failed",
+		);
+		assert_eq!(
+			MinerError::Feasibility(FeasibilityError::OverStaked).to_string(),
+			"feasibility error: voter is over-staked",
+		);
+
+		let feasibility = vec![
+			(FeasibilityError::WrongWinnerCount, "wrong number of winners"),
+			(FeasibilityError::SnapshotUnavailable, "snapshot unavailable"),
+			(FeasibilityError::NposElection(npos()), npos_message.as_str()),
+			(FeasibilityError::InvalidVote, "invalid vote"),
+			(FeasibilityError::InvalidVoter, "invalid voter"),
+			(FeasibilityError::DuplicateVoter, "duplicate voter"),
+			(FeasibilityError::OverStaked, "voter is over-staked"),
+			(FeasibilityError::DuplicateTargetInDistribution, "duplicate target in distribution"),
+			(FeasibilityError::WitnessCompactMismatch, "solution does not match witness"),
+			(FeasibilityError::SupportCapExceeded, "winner support cap exceeded"),
+			(FeasibilityError::InvalidWinner, "invalid winner"),
+			(FeasibilityError::InvalidScore, "invalid score"),
+			(FeasibilityError::InvalidRound, "invalid round"),
+		];
+		for (error, expected) in feasibility {
+			assert_eq!(error.to_string(), expected);
+		}
+	}
+
 	#[test]
 	fn number_of_voters_allowed_2sec_block() {
 		// Just a rough estimate with the substrate weights.
@@ -0,0 +1,179 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migrations of the pallet.
+
+use super::*;
+use frame_support::storage::{generator::StorageValue as _, migration};
+
+/// A value placed in storage that represents the current version of the storage of the pallet.
+///
+/// This is used by the `on_runtime_upgrade` logic to determine whether a storage migration needs
+/// to run.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+	/// The snapshot is stored as a single value, queued solutions carry no round.
+	V1_0_0,
+	/// The snapshot is stored in pages, queued solutions carry their round.
+	V2_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
+	}
+}
+
+/// [`ReadySolution`] as of [`Releases::V1_0_0`].
+#[derive(Decode)]
+struct OldReadySolution<A> {
+	supports: Supports<A>,
+	score: ElectionScore,
+	compute: ElectionCompute,
+}
+
+/// [`RoundSnapshot`] as of [`Releases::V1_0_0`].
+#[derive(Decode)]
+struct OldRoundSnapshot<A> {
+	voters: Vec<(A, VoteWeight, Vec<A>)>,
+	targets: Vec<A>,
+}
+
+/// Migrate the storage of the pallet from [`Releases::V1_0_0`] to [`Releases::V2_0_0`].
+///
+/// - The single `Snapshot` value, along with `DesiredTargets`, is moved into the pages of
+///   [`SnapshotTargets`] and [`SnapshotVoters`].
+/// - The [`QueuedSolution`] is assigned to the current round.
+pub fn migrate_to_v2<T: Config>() -> Weight {
+	let pallet = <Round<T>>::module_prefix();
+	let mut weight = T::DbWeight::get().reads_writes(4, 3);
+
+	let old_snapshot =
+		migration::take_storage_value::<OldRoundSnapshot<T::AccountId>>(pallet, b"Snapshot", &[]);
+	let old_desired_targets =
+		migration::take_storage_value::<u32>(pallet, b"DesiredTargets", &[]);
+	match old_snapshot {
+		Some(OldRoundSnapshot { voters, targets }) => {
+			let desired_targets =
+				old_desired_targets.unwrap_or_else(T::DataProvider::desired_targets);
+			<Pallet<T>>::put_snapshot(RoundSnapshot { voters, targets, desired_targets });
+			let pages = <Pallet<T>>::snapshot_pages().unwrap_or_default() as Weight;
+			weight = weight.saturating_add(T::DbWeight::get().writes(pages.saturating_add(6)));
+		}
+		// the metadata of a snapshot that no longer exists.
+		None if !<SnapshotTargets<T>>::exists() => <SnapshotMetadata<T>>::kill(),
+		None => (),
+	}
+
+	let round = <Pallet<T>>::round();
+	let _ = <QueuedSolution<T>>::translate::<OldReadySolution<T::AccountId>, _>(|old| {
+		old.map(|OldReadySolution { supports, score, compute }| ReadySolution {
+			supports,
+			score,
+			compute,
+			round,
+		})
+	});
+
+	log!(info, "migrated the storage to {:?}.", Releases::V2_0_0);
+	weight
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::*;
+	use frame_support::traits::OnRuntimeUpgrade;
+	use sp_npos_elections::Support;
+
+	fn put_old<V: Encode>(item: &[u8], value: V) {
+		migration::put_storage_value(<Round<Runtime>>::module_prefix(), item, &[], value);
+	}
+
+	fn get_old<V: Decode>(item: &[u8]) -> Option<V> {
+		migration::get_storage_value(<Round<Runtime>>::module_prefix(), item, &[])
+	}
+
+	#[test]
+	fn migrate_to_v2_works() {
+		ExtBuilder::default().voter_snapshot_per_page(3).build_and_execute(|| {
+			let voters = Voters::get();
+			let supports = vec![(10, Support { total: 20, voters: vec![(1, 20)] })];
+			let score = [20, 20, 400];
+			assert_eq!(<StorageVersion<Runtime>>::get(), Releases::V1_0_0);
+
+			// the storage as of v1, in the middle of round 2.
+			<Round<Runtime>>::put(2);
+			<CurrentPhase<Runtime>>::put(Phase::Signed);
+			put_old(b"Snapshot", (voters.clone(), Targets::get()));
+			put_old(b"DesiredTargets", 3u32);
+			<SnapshotMetadata<Runtime>>::put(SolutionOrSnapshotSize { voters: 8, targets: 4 });
+			sp_io::storage::set(
+				&<QueuedSolution<Runtime>>::hashed_key(),
+				&(supports.clone(), score, ElectionCompute::Unsigned).encode(),
+			);
+			assert!(MultiPhase::queued_solution().is_none());
+
+			MultiPhase::on_runtime_upgrade();
+			assert_eq!(<StorageVersion<Runtime>>::get(), Releases::V2_0_0);
+
+			// the snapshot is paged, the old items are gone.
+			assert_eq!(
+				MultiPhase::snapshot(),
+				Some(RoundSnapshot { voters, targets: Targets::get(), desired_targets: 3 }),
+			);
+			assert_eq!(MultiPhase::snapshot_pages(), Some(3));
+			assert!(get_old::<u32>(b"DesiredTargets").is_none());
+			assert!(get_old::<(Vec<u64>, Vec<u64>)>(b"Snapshot").is_none());
+
+			// the queued solution belongs to the current round.
+			assert_eq!(
+				MultiPhase::queued_solution(),
+				Some(ReadySolution {
+					supports,
+					score,
+					compute: ElectionCompute::Unsigned,
+					round: 2,
+				}),
+			);
+
+			// running it again does nothing.
+			let snapshot = MultiPhase::snapshot();
+			MultiPhase::on_runtime_upgrade();
+			assert_eq!(MultiPhase::snapshot(), snapshot);
+		})
+	}
+
+	#[test]
+	fn migrate_to_v2_without_snapshot_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// the metadata of a v1 snapshot is dropped along with it.
+			<SnapshotMetadata<Runtime>>::put(SolutionOrSnapshotSize { voters: 8, targets: 4 });
+			put_old(b"DesiredTargets", 3u32);
+
+			MultiPhase::on_runtime_upgrade();
+			assert_eq!(<StorageVersion<Runtime>>::get(), Releases::V2_0_0);
+			assert!(MultiPhase::snapshot_metadata().is_none());
+			assert!(get_old::<u32>(b"DesiredTargets").is_none());
+			assert!(MultiPhase::queued_solution().is_none());
+
+			// and the next round goes ahead as usual.
+			roll_to(15);
+			assert!(MultiPhase::snapshot().is_some());
+		})
+	}
+}
@@ -27,7 +27,7 @@ use parking_lot::RwLock;
 use sp_core::{
 	offchain::{
 		testing::{PoolState, TestOffchainExt, TestTransactionPoolExt},
-		OffchainDbExt, OffchainWorkerExt, TransactionPoolExt,
+		OffchainDbExt, OffchainWorkerExt, TransactionPool, TransactionPoolExt,
 	},
 	H256,
 };
@@ -79,6 +79,7 @@ pub(crate) fn multi_phase_events() -> Vec<super::Event<Runtime>> {
 pub fn roll_to(n: u64) {
 	let now = System::block_number();
 	for i in now + 1..=n {
+		MultiPhase::on_finalize(i - 1);
 		System::set_block_number(i);
 		MultiPhase::on_initialize(i);
 	}
@@ -87,6 +88,7 @@ pub fn roll_to(n: u64) {
 pub fn roll_to_with_ocw(n: u64) {
 	let now = System::block_number();
 	for i in now + 1..=n {
+		MultiPhase::on_finalize(i - 1);
 		System::set_block_number(i);
 		MultiPhase::on_initialize(i);
 		MultiPhase::offchain_worker(i);
@@ -97,8 +99,7 @@ pub fn roll_to_with_ocw(n: u64) {
 ///
 /// This is a good example of what an offchain miner would do.
 pub fn raw_solution() -> RawSolution<CompactOf<Runtime>> {
-	let RoundSnapshot { voters, targets } = MultiPhase::snapshot().unwrap();
-	let desired_targets = MultiPhase::desired_targets().unwrap();
+	let RoundSnapshot { voters, targets, desired_targets } = MultiPhase::snapshot().unwrap();
 
 	// closures
 	let cache = helpers::generate_voter_cache::<Runtime>(&voters);
@@ -193,17 +194,25 @@ parameter_types! {
 	];
 
 	pub static Fallback: FallbackStrategy = FallbackStrategy::OnChain;
+	pub static HistoryDepth: u32 = 4;
 	pub static DesiredTargets: u32 = 2;
 	pub static SignedPhase: u64 = 10;
 	pub static UnsignedPhase: u64 = 5;
+	pub static PostElectionCooldown: u64 = 0;
 	pub static MaxSignedSubmissions: u32 = 5;
 
 	pub static MinerMaxIterations: u32 = 5;
+	pub static MinerSolver: SolverStrategy = SolverStrategy::SeqPhragmen;
 	pub static MinerTxPriority: u64 = 100;
+	pub static PriorityStrategy: crate::PriorityStrategy = crate::PriorityStrategy::ScoreWeighted;
 	pub static SolutionImprovementThreshold: Perbill = Perbill::zero();
+	pub static BetterUnsignedThreshold: Perbill = Perbill::zero();
+	pub static MaxSupportPerTarget: Option<Perbill> = None;
 	pub static MinerMaxWeight: Weight = BlockWeights::get().max_block;
 	pub static MockWeightInfo: bool = false;
-
+	pub static PoolFailures: u32 = 0;
+	pub static ElectionsCompleted: u32 = 0;
+	pub static VoterSnapshotPerPage: u32 = u32::max_value();
 
 	pub static EpochLength: u64 = 30;
 }
@@ -264,15 +273,23 @@ impl crate::Config for Runtime {
 	type Currency = Balances;
 	type SignedPhase = SignedPhase;
 	type UnsignedPhase = UnsignedPhase;
+	type PostElectionCooldown = PostElectionCooldown;
 	type SolutionImprovementThreshold = SolutionImprovementThreshold;
+	type BetterUnsignedThreshold = BetterUnsignedThreshold;
+	type MaxSupportPerTarget = MaxSupportPerTarget;
+	type VoterSnapshotPerPage = VoterSnapshotPerPage;
 	type MinerMaxIterations = MinerMaxIterations;
+	type MinerSolver = MinerSolver;
 	type MinerMaxWeight = MinerMaxWeight;
 	type MinerTxPriority = MinerTxPriority;
+	type PriorityStrategy = PriorityStrategy;
 	type DataProvider = StakingMock;
 	type WeightInfo = DualMockWeightInfo;
 	type BenchmarkingConfig = ();
 	type OnChainAccuracy = Perbill;
 	type Fallback = Fallback;
+	type HistoryDepth = HistoryDepth;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
 	type CompactSolution = TestCompact;
 }
 
@@ -303,6 +320,9 @@ impl ElectionDataProvider<AccountId, u64> for StakingMock {
 	fn next_election_prediction(now: u64) -> u64 {
 		now + EpochLength::get() - now % EpochLength::get()
 	}
+	fn on_election_completed() {
+		<ElectionsCompleted>::set(ElectionsCompleted::get() + 1);
+	}
 }
 
 impl ExtBuilder {
@@ -310,19 +330,43 @@ impl ExtBuilder {
 		<MinerTxPriority>::set(p);
 		self
 	}
+	pub fn miner_solver(self, solver: SolverStrategy) -> Self {
+		<MinerSolver>::set(solver);
+		self
+	}
+	pub fn priority_strategy(self, strategy: crate::PriorityStrategy) -> Self {
+		<PriorityStrategy>::set(strategy);
+		self
+	}
 	pub fn solution_improvement_threshold(self, p: Perbill) -> Self {
 		<SolutionImprovementThreshold>::set(p);
 		self
 	}
+	pub fn better_unsigned_threshold(self, p: Perbill) -> Self {
+		<BetterUnsignedThreshold>::set(p);
+		self
+	}
+	pub fn voter_snapshot_per_page(self, p: u32) -> Self {
+		<VoterSnapshotPerPage>::set(p);
+		self
+	}
 	pub fn phases(self, signed: u64, unsigned: u64) -> Self {
 		<SignedPhase>::set(signed);
 		<UnsignedPhase>::set(unsigned);
 		self
 	}
+	pub fn post_election_cooldown(self, cooldown: u64) -> Self {
+		<PostElectionCooldown>::set(cooldown);
+		self
+	}
 	pub fn fallabck(self, fallback: FallbackStrategy) -> Self {
 		<Fallback>::set(fallback);
 		self
 	}
+	pub fn history_depth(self, depth: u32) -> Self {
+		<HistoryDepth>::set(depth);
+		self
+	}
 	pub fn miner_weight(self, weight: Weight) -> Self {
 		<MinerMaxWeight>::set(weight);
 		self
@@ -331,6 +375,10 @@ impl ExtBuilder {
 		<MockWeightInfo>::set(mock);
 		self
 	}
+	pub fn pool_failures(self, failures: u32) -> Self {
+		<PoolFailures>::set(failures);
+		self
+	}
 	pub fn desired_targets(self, t: u32) -> Self {
 		<DesiredTargets>::set(t);
 		self
@@ -364,6 +412,7 @@ impl ExtBuilder {
 		let mut ext = self.build();
 		let (offchain, offchain_state) = TestOffchainExt::new();
 		let (pool, pool_state) = TestTransactionPoolExt::new();
+		let pool = FlakyTransactionPool(pool);
 
 		let mut seed = [0_u8; 32];
 		seed[0..4].copy_from_slice(&iters.to_le_bytes());
@@ -380,3 +429,17 @@ impl ExtBuilder {
 		self.build().execute_with(test)
 	}
 }
+
+/// A transaction pool that rejects the first [`PoolFailures`] submissions.
+pub struct FlakyTransactionPool(TestTransactionPoolExt);
+
+impl TransactionPool for FlakyTransactionPool {
+	fn submit_transaction(&mut self, extrinsic: Vec<u8>) -> Result<(), ()> {
+		let failures = <PoolFailures>::get();
+		if failures > 0 {
+			<PoolFailures>::set(failures - 1);
+			return Err(());
+		}
+		self.0.submit_transaction(extrinsic)
+	}
+}
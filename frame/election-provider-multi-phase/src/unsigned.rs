@@ -21,25 +21,60 @@ use crate::*;
 use frame_support::dispatch::DispatchResult;
 use frame_system::offchain::SubmitTransaction;
 use sp_npos_elections::{
-	seq_phragmen, CompactSolution, ElectionResult, assignment_ratio_to_staked_normalized,
+	phragmms, seq_phragmen, CompactSolution, ElectionResult, assignment_ratio_to_staked_normalized,
 	assignment_staked_to_ratio_normalized,
 };
-use sp_runtime::{offchain::storage::StorageValueRef, traits::TrailingZeroInput};
+use sp_runtime::{
+	offchain::{storage::StorageValueRef, Duration},
+	traits::TrailingZeroInput,
+};
 use sp_std::cmp::Ordering;
 
 /// Storage key used to store the persistent offchain worker status.
 pub(crate) const OFFCHAIN_HEAD_DB: &[u8] = b"parity/multi-phase-unsigned-election";
 
+/// Storage key used to cache the last solution mined by the offchain worker.
+pub(crate) const OFFCHAIN_CACHED_SOLUTION: &[u8] = b"parity/multi-phase-unsigned-election/solution";
+
+/// Storage key used to count the successful executions of the offchain miner on this node.
+pub(crate) const OFFCHAIN_MINED_TOTAL: &[u8] = b"parity/multi-phase-unsigned-election/mined";
+
+/// Storage key used to count the failed executions of the offchain miner on this node.
+pub(crate) const OFFCHAIN_FAILED_TOTAL: &[u8] = b"parity/multi-phase-unsigned-election/failed";
+
 /// The repeat threshold of the offchain worker. This means we won't run the offchain worker twice
 /// within a window of 5 blocks.
 pub(crate) const OFFCHAIN_REPEAT: u32 = 5;
 
+/// The number of times the offchain worker attempts to submit a solution to the pool.
+pub(crate) const OFFCHAIN_SUBMIT_ATTEMPTS: u32 = 3;
+
+/// The delay before the first re-submission to the pool, in milliseconds. Doubled for each further
+/// attempt, such that all retries together wait at most 150ms.
+pub(crate) const OFFCHAIN_SUBMIT_BACKOFF: u64 = 50;
+
+/// The outcome of [`Pallet::try_acquire_offchain_lock`].
+#[must_use]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum OffchainExecutionDecision {
+	/// The offchain worker may run at this block.
+	Proceed,
+	/// The block is older than the last one the offchain worker ran at, likely a fork.
+	SkipFork,
+	/// The offchain worker has already run within the last [`OFFCHAIN_REPEAT`] blocks.
+	SkipRecent,
+	/// The block could not be recorded in the offchain db.
+	WriteFailed,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum MinerError {
 	/// An internal error in the NPoS elections crate.
 	NposElections(sp_npos_elections::Error),
 	/// Snapshot data was unavailable unexpectedly.
 	SnapshotUnAvailable,
+	/// The solver failed to compute a solution.
+	Solver(&'static str),
 	/// Submitting a transaction to the pool failed.
 	PoolSubmissionFailed,
 	/// The pre-dispatch checks failed for the mined solution.
@@ -60,16 +95,134 @@ impl From<FeasibilityError> for MinerError {
 	}
 }
 
+impl sp_std::fmt::Display for MinerError {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		match self {
+			MinerError::NposElections(e) => write!(f, "npos elections error: {:?}", e),
+			MinerError::SnapshotUnAvailable => write!(f, "snapshot unavailable"),
+			MinerError::Solver(e) => write!(f, "solver error: {}", e),
+			MinerError::PoolSubmissionFailed => write!(f, "submitting to the pool failed"),
+			MinerError::PreDispatchChecksFailed => write!(f, "pre-dispatch checks failed"),
+			MinerError::Feasibility(e) => write!(f, "feasibility error: {}", e),
+		}
+	}
+}
+
 impl<T: Config> Pallet<T> {
 	/// Mine a new solution, and submit it back to the chain as an unsigned transaction.
+	///
+	/// A solution mined in a previous execution of the same round is re-used, if it can still be
+	/// submitted. The outcome is counted in the offchain db, see [`Pallet::offchain_miner_totals`].
 	pub fn mine_check_and_submit() -> Result<(), MinerError> {
-		let iters = Self::get_balancing_iters();
+		// the solution of this node has already made it on chain, there is nothing left to do.
+		if Self::cached_solution_is_queued() {
+			log!(debug, "cached solution of round {} is already queued.", Self::round());
+			return Ok(());
+		}
+
 		// get the solution, with a load of checks to ensure if submitted, IT IS ABSOLUTELY VALID.
-		let (raw_solution, witness) = Self::mine_and_check(iters)?;
+		let outcome = Self::restore_or_mine_and_check().and_then(|(raw_solution, witness)| {
+			let call = Call::submit_unsigned(raw_solution, witness);
+			Self::submit_call(call)
+		});
+
+		Self::note_miner_outcome(outcome.is_ok());
+		outcome
+	}
+
+	/// The number of successful and failed executions of the offchain miner on this node, as
+	/// `(mined, failed)`.
+	///
+	/// These are node-local diagnostics, kept in the persistent offchain db.
+	pub fn offchain_miner_totals() -> (u32, u32) {
+		let total_of =
+			|key| StorageValueRef::persistent(key).get::<u32>().flatten().unwrap_or_default();
+		(total_of(OFFCHAIN_MINED_TOTAL), total_of(OFFCHAIN_FAILED_TOTAL))
+	}
+
+	/// Increment the offchain counter of successful or failed miner executions.
+	fn note_miner_outcome(success: bool) {
+		let key = if success { OFFCHAIN_MINED_TOTAL } else { OFFCHAIN_FAILED_TOTAL };
+		// a lost update only makes the diagnostics slightly off, nothing to do about it.
+		let _ = StorageValueRef::persistent(key).mutate::<u32, (), _>(|total| {
+			Ok(total.flatten().unwrap_or_default().saturating_add(1))
+		});
+	}
+
+	/// Submit `call` to the pool as an unsigned transaction.
+	///
+	/// A failed submission is retried up to [`OFFCHAIN_SUBMIT_ATTEMPTS`] times in total, with an
+	/// exponential backoff starting at [`OFFCHAIN_SUBMIT_BACKOFF`].
+	fn submit_call(call: Call<T>) -> Result<(), MinerError> {
+		let mut attempt = 1;
+		loop {
+			match SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.clone().into())
+			{
+				Ok(_) => return Ok(()),
+				Err(_) if attempt < OFFCHAIN_SUBMIT_ATTEMPTS => {
+					let delay = OFFCHAIN_SUBMIT_BACKOFF << (attempt - 1);
+					log!(debug, "submission attempt {} failed, retrying in {}ms", attempt, delay);
+					let now = sp_io::offchain::timestamp();
+					sp_io::offchain::sleep_until(now.add(Duration::from_millis(delay)));
+					attempt += 1;
+				}
+				Err(_) => return Err(MinerError::PoolSubmissionFailed),
+			}
+		}
+	}
+
+	/// Restore the solution cached by a previous execution of the offchain worker, if it was mined
+	/// against the current snapshot and still passes the pre-dispatch checks (i.e. no better
+	/// solution has been queued since). Else, mine a new one via [`Pallet::mine_and_check`] and
+	/// cache it.
+	///
+	/// The cache is keyed by the hash of the snapshot rather than just the round: a re-org can
+	/// replace the snapshot within the same round, in which case we must re-mine. A solution that
+	/// was mined against the current snapshot need not be checked for feasibility again.
+	fn restore_or_mine_and_check(
+	) -> Result<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize), MinerError> {
+		let storage = StorageValueRef::persistent(&OFFCHAIN_CACHED_SOLUTION);
+		let snapshot_hash = Self::snapshot().map(|snapshot| T::Hashing::hash_of(&snapshot));
+		let cached = storage
+			.get::<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize, T::Hash)>()
+			.flatten()
+			.filter(|(solution, _, _)| solution.round == Self::round())
+			.filter(|(_, _, hash)| Some(*hash) == snapshot_hash)
+			.filter(|(solution, _, _)| Self::unsigned_pre_dispatch_checks(solution).is_ok());
+
+		match cached {
+			Some((solution, witness, _)) => {
+				log!(debug, "restored cached solution of round {}", solution.round);
+				Ok((solution, witness))
+			}
+			None => {
+				let iters = Self::get_balancing_iters();
+				let (solution, witness) = Self::mine_and_check(iters)?;
+				// `mine_and_check` only succeeds if there is a snapshot.
+				if let Some(hash) = snapshot_hash {
+					storage.set(&(&solution, witness, hash));
+				}
+				Ok((solution, witness))
+			}
+		}
+	}
 
-		let call = Call::submit_unsigned(raw_solution, witness).into();
-		SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call)
-			.map_err(|_| MinerError::PoolSubmissionFailed)
+	/// Returns `true` if the solution cached by a previous execution of the offchain worker belongs
+	/// to the current round, and is the queued one.
+	///
+	/// Such a solution would fail the pre-dispatch checks as a duplicate of the queued one.
+	fn cached_solution_is_queued() -> bool {
+		let queued_hash = match Self::queued_solution_hash() {
+			Some(hash) => hash,
+			None => return false,
+		};
+		StorageValueRef::persistent(&OFFCHAIN_CACHED_SOLUTION)
+			.get::<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize, T::Hash)>()
+			.flatten()
+			.map_or(false, |(solution, _, _)| {
+				solution.round == Self::round()
+					&& T::Hashing::hash_of(&solution.compact) == queued_hash
+			})
 	}
 
 	/// Mine a new npos solution, with all the relevant checks to make sure that it will be accepted
@@ -100,28 +253,51 @@ impl<T: Config> Pallet<T> {
 		Ok((raw_solution, witness))
 	}
 
-	/// Mine a new npos solution.
+	/// Mine a new npos solution, with the solver of [`Config::MinerSolver`].
 	pub fn mine_solution(
 		iters: usize,
 	) -> Result<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize), MinerError> {
-		let RoundSnapshot { voters, targets } =
+		let RoundSnapshot { voters, targets, .. } =
 			Self::snapshot().ok_or(MinerError::SnapshotUnAvailable)?;
 		let desired_targets = Self::desired_targets().ok_or(MinerError::SnapshotUnAvailable)?;
 
-		seq_phragmen::<_, CompactAccuracyOf<T>>(
-			desired_targets as usize,
-			targets,
-			voters,
-			Some((iters, 0)),
-		)
-		.map_err(Into::into)
+		// defensive: never ask the solver for more winners than there are targets.
+		let desired_winners = Self::desired_winners(desired_targets, targets.len() as u32);
+		if desired_winners < desired_targets {
+			log!(
+				warn,
+				"miner: desired targets ({}) clamped to the number of targets ({})",
+				desired_targets,
+				desired_winners,
+			);
+		}
+		let desired_targets = desired_winners;
+
+		match T::MinerSolver::get() {
+			SolverStrategy::SeqPhragmen => seq_phragmen::<_, CompactAccuracyOf<T>>(
+				desired_targets as usize,
+				targets,
+				voters,
+				Some((iters, 0)),
+			)
+			.map_err(Into::into),
+			SolverStrategy::PhragMMS => phragmms::<_, CompactAccuracyOf<T>>(
+				desired_targets as usize,
+				targets,
+				voters,
+				Some((iters, 0)),
+			)
+			.map_err(MinerError::Solver),
+		}
 		.and_then(Self::prepare_election_result)
 	}
 
 	/// Convert a raw solution from [`sp_npos_elections::ElectionResult`] to [`RawSolution`], which
 	/// is ready to be submitted to the chain.
 	///
-	/// Will always reduce the solution as well.
+	/// Will always reduce the solution as well. The score is computed against the winners as they
+	/// appear in the compact solution, in the same canonical order used by
+	/// [`Pallet::feasibility_check`], regardless of the order of `election_result.winners`.
 	pub fn prepare_election_result(
 		election_result: ElectionResult<T::AccountId, CompactAccuracyOf<T>>,
 	) -> Result<(RawSolution<CompactOf<T>>, SolutionOrSnapshotSize), MinerError> {
@@ -129,7 +305,7 @@ impl<T: Config> Pallet<T> {
 		// some point though.
 
 		// storage items. Note: we have already read this from storage, they must be in cache.
-		let RoundSnapshot { voters, targets } =
+		let RoundSnapshot { voters, targets, .. } =
 			Self::snapshot().ok_or(MinerError::SnapshotUnAvailable)?;
 		let desired_targets = Self::desired_targets().ok_or(MinerError::SnapshotUnAvailable)?;
 
@@ -141,7 +317,8 @@ impl<T: Config> Pallet<T> {
 		let target_at = helpers::target_at_fn::<T>(&targets);
 		let stake_of = helpers::stake_of_fn::<T>(&voters, &cache);
 
-		let ElectionResult { assignments, winners } = election_result;
+		// the winners are re-derived from the final compact solution below.
+		let ElectionResult { assignments, .. } = election_result;
 
 		// convert to staked and reduce.
 		let mut staked = assignment_ratio_to_staked_normalized(assignments, &stake_of)
@@ -155,7 +332,7 @@ impl<T: Config> Pallet<T> {
 		let size =
 			SolutionOrSnapshotSize { voters: voters.len() as u32, targets: targets.len() as u32 };
 		let maximum_allowed_voters = Self::maximum_voter_for_weight::<T::WeightInfo>(
-			desired_targets,
+			Self::desired_winners(desired_targets, size.targets),
 			size,
 			T::MinerMaxWeight::get(),
 		);
@@ -167,8 +344,12 @@ impl<T: Config> Pallet<T> {
 		);
 		let compact = Self::trim_compact(maximum_allowed_voters, compact, &voter_index)?;
 
-		// re-calc score.
-		let winners = sp_npos_elections::to_without_backing(winners);
+		// re-calc score, with the winners extracted exactly like the feasibility check does.
+		let winners = compact
+			.unique_targets()
+			.into_iter()
+			.map(|i| target_at(i).ok_or(sp_npos_elections::Error::CompactInvalidIndex))
+			.collect::<Result<Vec<_>, _>>()?;
 		let score = compact.clone().score(&winners, stake_of, voter_at, target_at)?;
 
 		let round = Self::round();
@@ -218,14 +399,15 @@ impl<T: Config> Pallet<T> {
 	{
 		match compact.voter_count().checked_sub(maximum_allowed_voters as usize) {
 			Some(to_remove) if to_remove > 0 => {
-				// grab all voters and sort them by least stake.
+				// grab all voters and sort them by least stake. Ties are broken by account id, so
+				// that the same voters are removed regardless of the order of the snapshot.
 				let RoundSnapshot { voters, .. } =
 					Self::snapshot().ok_or(MinerError::SnapshotUnAvailable)?;
 				let mut voters_sorted = voters
 					.into_iter()
 					.map(|(who, stake, _)| (who.clone(), stake))
 					.collect::<Vec<_>>();
-				voters_sorted.sort_by_key(|(_, y)| *y);
+				voters_sorted.sort_by(|(a, x), (b, y)| x.cmp(y).then_with(|| a.cmp(b)));
 
 				// start removing from the least stake. Iterate until we know enough have been
 				// removed.
@@ -272,6 +454,17 @@ impl<T: Config> Pallet<T> {
 			W::submit_unsigned(size.voters, size.targets, active_voters, desired_winners)
 		};
 
+		// the search below assumes that the weight only grows with the number of voters.
+		debug_assert!(
+			weight_with(0) <= weight_with(max_voters),
+			"weight must be monotonic in the number of active voters",
+		);
+
+		// if the base weight of a solution (i.e. with no voters) is already too much, nothing fits.
+		if weight_with(0) > max_weight {
+			return 0;
+		}
+
 		let next_voters = |current_weight: Weight, voters: u32, step: u32| -> Result<u32, ()> {
 			match current_weight.cmp(&max_weight) {
 				Ordering::Less => {
@@ -330,17 +523,18 @@ impl<T: Config> Pallet<T> {
 	/// This essentially makes sure that we don't run on previous blocks in case of a re-org, and we
 	/// don't run twice within a window of length [`OFFCHAIN_REPEAT`].
 	///
-	/// Returns `Ok(())` if offchain worker should happen, `Err(reason)` otherwise.
-	pub(crate) fn try_acquire_offchain_lock(now: T::BlockNumber) -> Result<(), &'static str> {
+	/// Returns [`OffchainExecutionDecision::Proceed`] if offchain worker should happen, or the
+	/// reason for which it should not otherwise.
+	pub(crate) fn try_acquire_offchain_lock(now: T::BlockNumber) -> OffchainExecutionDecision {
 		let storage = StorageValueRef::persistent(&OFFCHAIN_HEAD_DB);
 		let threshold = T::BlockNumber::from(OFFCHAIN_REPEAT);
 
-		let mutate_stat =
-			storage.mutate::<_, &'static str, _>(|maybe_head: Option<Option<T::BlockNumber>>| {
+		let mutate_stat = storage.mutate::<_, OffchainExecutionDecision, _>(
+			|maybe_head: Option<Option<T::BlockNumber>>| {
 				match maybe_head {
-					Some(Some(head)) if now < head => Err("fork."),
+					Some(Some(head)) if now < head => Err(OffchainExecutionDecision::SkipFork),
 					Some(Some(head)) if now >= head && now <= head + threshold => {
-						Err("recently executed.")
+						Err(OffchainExecutionDecision::SkipRecent)
 					}
 					Some(Some(head)) if now > head + threshold => {
 						// we can run again now. Write the new head.
@@ -351,15 +545,29 @@ impl<T: Config> Pallet<T> {
 						Ok(now)
 					}
 				}
-			});
+			},
+		);
 
 		match mutate_stat {
 			// all good
-			Ok(Ok(_)) => Ok(()),
+			Ok(Ok(_)) => OffchainExecutionDecision::Proceed,
 			// failed to write.
-			Ok(Err(_)) => Err("failed to write to offchain db."),
+			Ok(Err(_)) => OffchainExecutionDecision::WriteFailed,
 			// fork etc.
-			Err(why) => Err(why),
+			Err(decision) => decision,
+		}
+	}
+
+	/// The pool priority of an unsigned solution with the given `score`, as dictated by
+	/// [`Config::PriorityStrategy`].
+	pub(crate) fn unsigned_priority(score: &ElectionScore) -> TransactionPriority {
+		match T::PriorityStrategy::get() {
+			PriorityStrategy::Fixed(priority) => priority,
+			// The higher the score[0], the better a solution is.
+			PriorityStrategy::ScoreWeighted => {
+				T::MinerTxPriority::get().saturating_add(score[0].saturated_into())
+			}
+			PriorityStrategy::AlwaysTop => TransactionPriority::max_value(),
 		}
 	}
 
@@ -376,19 +584,32 @@ impl<T: Config> Pallet<T> {
 		// ensure solution is timely. Don't panic yet. This is a cheap check.
 		ensure!(Self::current_phase().is_unsigned_open(), Error::<T>::PreDispatchEarlySubmission);
 
+		// ensure solution is not left over from a previous round, e.g. lingering in the pool.
+		ensure!(solution.round == Self::round(), Error::<T>::PreDispatchWrongRound);
+
 		// ensure correct number of winners.
+		let desired_winners = Self::desired_winners(
+			Self::desired_targets().unwrap_or_default(),
+			Self::snapshot_metadata().unwrap_or_default().targets,
+		);
 		ensure!(
-			Self::desired_targets().unwrap_or_default()
-				== solution.compact.unique_targets().len() as u32,
+			desired_winners == solution.compact.unique_targets().len() as u32,
 			Error::<T>::PreDispatchWrongWinnerCount,
 		);
 
+		// ensure this is not the very same solution that is already queued.
+		ensure!(
+			Self::queued_solution_hash()
+				.map_or(true, |h| h != T::Hashing::hash_of(&solution.compact)),
+			Error::<T>::PreDispatchDuplicateSubmission,
+		);
+
 		// ensure score is being improved. Panic henceforth.
 		ensure!(
 			Self::queued_solution().map_or(true, |q: ReadySolution<_>| is_score_better::<Perbill>(
 				solution.score,
 				q.score,
-				T::SolutionImprovementThreshold::get()
+				T::BetterUnsignedThreshold::get()
 			)),
 			Error::<T>::PreDispatchWeakSubmission,
 		);
@@ -424,6 +645,28 @@ mod max_weight {
 		}
 	}
 
+	struct TestWeightWithBase;
+	impl crate::weights::WeightInfo for TestWeightWithBase {
+		fn on_initialize_nothing() -> Weight {
+			unreachable!()
+		}
+		fn on_initialize_open_signed() -> Weight {
+			unreachable!()
+		}
+		fn on_initialize_open_unsigned_with_snapshot() -> Weight {
+			unreachable!()
+		}
+		fn on_initialize_open_unsigned_without_snapshot() -> Weight {
+			unreachable!()
+		}
+		fn submit_unsigned(v: u32, t: u32, a: u32, d: u32) -> Weight {
+			(5000 + 1000 * a) as Weight
+		}
+		fn feasibility_check(v: u32, _t: u32, a: u32, d: u32) -> Weight {
+			unreachable!()
+		}
+	}
+
 	#[test]
 	fn find_max_voter_binary_search_works() {
 		let w = SolutionOrSnapshotSize { voters: 10, targets: 0 };
@@ -477,6 +720,24 @@ mod max_weight {
 		assert_eq!(MultiPhase::maximum_voter_for_weight::<TestWeight>(0, w, 2010), 2);
 		assert_eq!(MultiPhase::maximum_voter_for_weight::<TestWeight>(0, w, 3333), 2);
 	}
+
+	#[test]
+	fn find_max_voter_binary_search_with_base_weight_works() {
+		let w = SolutionOrSnapshotSize { voters: 10, targets: 0 };
+
+		// base weight alone does not fit.
+		assert_eq!(MultiPhase::maximum_voter_for_weight::<TestWeightWithBase>(0, w, 0), 0);
+		assert_eq!(MultiPhase::maximum_voter_for_weight::<TestWeightWithBase>(0, w, 4999), 0);
+
+		// base weight fits, but no voter on top of it.
+		assert_eq!(MultiPhase::maximum_voter_for_weight::<TestWeightWithBase>(0, w, 5000), 0);
+		assert_eq!(MultiPhase::maximum_voter_for_weight::<TestWeightWithBase>(0, w, 5999), 0);
+
+		assert_eq!(MultiPhase::maximum_voter_for_weight::<TestWeightWithBase>(0, w, 6000), 1);
+		assert_eq!(MultiPhase::maximum_voter_for_weight::<TestWeightWithBase>(0, w, 9999), 4);
+		assert_eq!(MultiPhase::maximum_voter_for_weight::<TestWeightWithBase>(0, w, 15_000), 10);
+		assert_eq!(MultiPhase::maximum_voter_for_weight::<TestWeightWithBase>(0, w, 20_000), 10);
+	}
 }
 
 #[cfg(test)]
@@ -488,6 +749,7 @@ mod tests {
 	use frame_support::{dispatch::Dispatchable, traits::OffchainWorker};
 	use mock::Call as OuterCall;
 	use sp_election_providers::Assignment;
+	use sp_core::H256;
 	use sp_runtime::{traits::ValidateUnsigned, PerU16};
 
 	#[test]
@@ -627,6 +889,37 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn priority_strategies() {
+		let priority_of = |strategy| {
+			let mut priority = 0;
+			ExtBuilder::default()
+				.miner_tx_priority(20)
+				.priority_strategy(strategy)
+				.desired_targets(0)
+				.build_and_execute(|| {
+					roll_to(25);
+					let solution =
+						RawSolution::<TestCompact> { score: [5, 0, 0], ..Default::default() };
+					let call = Call::submit_unsigned(solution, witness());
+					priority = <MultiPhase as ValidateUnsigned>::validate_unsigned(
+						TransactionSource::Local,
+						&call,
+					)
+					.unwrap()
+					.priority;
+				});
+			priority
+		};
+
+		assert_eq!(priority_of(crate::PriorityStrategy::ScoreWeighted), 25);
+		assert_eq!(priority_of(crate::PriorityStrategy::Fixed(7)), 7);
+		assert_eq!(
+			priority_of(crate::PriorityStrategy::AlwaysTop),
+			TransactionPriority::max_value(),
+		);
+	}
+
 	#[test]
 	#[should_panic(expected = "Invalid unsigned submission must produce invalid block and \
 	                           deprive validator from their authoring reward.: \
@@ -718,10 +1011,126 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn miner_trims_same_voters_regardless_of_order() {
+		let trimmed_voters = |reverse: bool| {
+			let mut trimmed = vec![];
+			ExtBuilder::default().miner_weight(25).mock_weight_info(true).build_and_execute(|| {
+				if reverse {
+					<Voters>::set(<Voters>::get().into_iter().rev().collect());
+				}
+				roll_to(25);
+				assert!(MultiPhase::current_phase().is_unsigned());
+
+				let (solution, _) = MultiPhase::mine_solution(2).unwrap();
+				assert_eq!(solution.compact.voter_count(), 3);
+
+				let RoundSnapshot { voters, targets, .. } = MultiPhase::snapshot().unwrap();
+				let voter_at = helpers::voter_at_fn::<Runtime>(&voters);
+				let target_at = helpers::target_at_fn::<Runtime>(&targets);
+				trimmed = solution
+					.compact
+					.into_assignment(voter_at, target_at)
+					.unwrap()
+					.into_iter()
+					.map(|a| a.who)
+					.collect::<Vec<_>>();
+				trimmed.sort();
+			});
+			trimmed
+		};
+
+		// voters 1, 2, 3, 4 and 10 all have the same stake.
+		assert_eq!(trimmed_voters(false), trimmed_voters(true));
+	}
+
+	/// A fixed snapshot used to check that the miner is deterministic.
+	fn deterministic_miner_fixture() -> (Vec<AccountId>, Vec<(AccountId, VoteWeight, Vec<AccountId>)>)
+	{
+		let targets = vec![10, 20, 30, 40, 50, 60];
+		let voters = vec![
+			(1, 17, vec![10, 20, 30]),
+			(2, 23, vec![20, 40]),
+			(3, 31, vec![30, 50, 60]),
+			(4, 9, vec![10, 60]),
+			(5, 44, vec![40, 50]),
+			(6, 12, vec![10, 30, 50]),
+			(7, 28, vec![20, 60]),
+			(8, 5, vec![40]),
+			(10, 10, vec![10]),
+			(20, 20, vec![20]),
+			(30, 30, vec![30]),
+			(40, 40, vec![40]),
+			(50, 50, vec![50]),
+			(60, 60, vec![60]),
+		];
+		(targets, voters)
+	}
+
+	#[test]
+	fn miner_score_is_deterministic() {
+		// The score of a mined solution is checked by every node, and thus must be the same on all
+		// platforms. The edges that `reduce` removes, and thus their order in the compact, are an
+		// implementation detail that may change, but the score that is derived from them must not.
+		let mine = |max_weight: Weight| {
+			let mut score = Default::default();
+			ExtBuilder::default()
+				.desired_targets(3)
+				.miner_weight(max_weight)
+				.mock_weight_info(true)
+				.build_and_execute(|| {
+					let (targets, voters) = deterministic_miner_fixture();
+					<Targets>::set(targets);
+					<Voters>::set(voters);
+					roll_to(25);
+					assert!(MultiPhase::current_phase().is_unsigned());
+
+					let (solution, _) = MultiPhase::mine_solution(2).unwrap();
+					score = solution.score;
+				});
+			score
+		};
+
+		// untrimmed.
+		assert_eq!(mine(Weight::max_value()), [99, 302, 30410]);
+		// trimmed, such that only some of the voters are kept.
+		assert_eq!(mine(40), [66, 225, 17141]);
+	}
+
+	#[test]
+	fn miner_solvers_work() {
+		let mine = |solver: SolverStrategy| {
+			let mut score = Default::default();
+			ExtBuilder::default().miner_solver(solver).desired_targets(3).build_and_execute(|| {
+				let (targets, voters) = deterministic_miner_fixture();
+				<Targets>::set(targets);
+				<Voters>::set(voters);
+				roll_to(25);
+				assert!(MultiPhase::current_phase().is_unsigned());
+
+				let (solution, _) = MultiPhase::mine_solution(2).unwrap();
+				assert_ok!(
+					MultiPhase::feasibility_check(solution.clone(), ElectionCompute::Unsigned)
+				);
+				score = solution.score;
+			});
+			score
+		};
+
+		let seq_phragmen = mine(SolverStrategy::SeqPhragmen);
+		let phragmms = mine(SolverStrategy::PhragMMS);
+		assert_eq!(seq_phragmen, [99, 302, 30410]);
+		// a slightly better minimal backing, and a more even distribution.
+		assert_eq!(phragmms, [100, 302, 30402]);
+		assert!(is_score_better(phragmms, seq_phragmen, Perbill::zero()));
+	}
+
 	#[test]
 	fn miner_will_not_submit_if_not_enough_winners() {
-		let (mut ext, _) = ExtBuilder::default().desired_targets(8).build_offchainify(0);
+		let (mut ext, _) = ExtBuilder::default().desired_targets(5).build_offchainify(0);
 		ext.execute_with(|| {
+			// 50 has no backing, so it cannot be a winner in the mined solution.
+			<Targets>::set(vec![10, 20, 30, 40, 50]);
 			roll_to(25);
 			assert!(MultiPhase::current_phase().is_unsigned());
 
@@ -733,13 +1142,70 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn miner_works_with_fewer_targets_than_desired() {
+		ExtBuilder::default().desired_targets(3).build_and_execute(|| {
+			<Targets>::set(vec![10, 20]);
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert_eq!(MultiPhase::desired_targets().unwrap(), 3);
+			assert_eq!(MultiPhase::snapshot().unwrap().targets.len(), 2);
+
+			// the best we can do is to elect both targets.
+			let (solution, witness) = MultiPhase::mine_and_check(2).unwrap();
+			assert_eq!(solution.compact.unique_targets().len(), 2);
+
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			let supports = MultiPhase::queued_solution().unwrap().supports;
+			assert_eq!(supports.iter().map(|(w, _)| *w).collect::<Vec<_>>(), vec![10, 20]);
+		})
+	}
+
+	#[test]
+	fn more_desired_targets_than_targets_is_accepted() {
+		ExtBuilder::default().desired_targets(8).build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// the snapshot was created with more desired targets than targets, which is only
+			// warned about.
+			let snapshot = MultiPhase::snapshot().unwrap();
+			assert_eq!(snapshot.desired_targets, 8);
+			assert_eq!(snapshot.targets.len(), 4);
+
+			// all of the targets are elected, and the solution is accepted.
+			let (solution, witness) = MultiPhase::mine_and_check(2).unwrap();
+			assert_eq!(solution.compact.unique_targets().len(), 4);
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+			assert_eq!(MultiPhase::queued_solution().unwrap().supports.len(), 4);
+		})
+	}
+
+	#[test]
+	fn miner_clamps_corrupt_desired_targets() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			// for whatever reason, the desired targets are way more than the targets.
+			<SnapshotTargets<Runtime>>::mutate(|s| s.as_mut().unwrap().1 = 1_000_000);
+
+			// the best we can do is to elect all of the targets.
+			let (solution, witness) = MultiPhase::mine_and_check(2).unwrap();
+			assert_eq!(solution.compact.unique_targets().len(), 4);
+
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution, witness));
+		})
+	}
+
 	#[test]
 	fn unsigned_per_dispatch_checks_can_only_submit_threshold_better() {
 		ExtBuilder::default()
 			.desired_targets(1)
 			.add_voter(7, 2, vec![10])
 			.add_voter(8, 5, vec![10])
-			.solution_improvement_threshold(Perbill::from_percent(50))
+			.solution_improvement_threshold(Perbill::from_percent(10))
+			.better_unsigned_threshold(Perbill::from_percent(50))
 			.build_and_execute(|| {
 				roll_to(25);
 				assert!(MultiPhase::current_phase().is_unsigned());
@@ -772,7 +1238,8 @@ mod tests {
 					],
 				};
 				let (solution, _) = MultiPhase::prepare_election_result(result).unwrap();
-				// 12 is not 50% more than 10
+				// 12 is not 50% more than 10. It would be good enough for the signed threshold, which
+				// does not apply to unsigned solutions.
 				assert_eq!(solution.score[0], 12);
 				assert_noop!(
 					MultiPhase::unsigned_pre_dispatch_checks(&solution),
@@ -802,6 +1269,55 @@ mod tests {
 			})
 	}
 
+	#[test]
+	fn score_does_not_depend_on_winner_order() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let RoundSnapshot { voters, targets, .. } = MultiPhase::snapshot().unwrap();
+			let ElectionResult { winners, assignments } =
+				seq_phragmen::<_, CompactAccuracyOf<Runtime>>(2, targets, voters, None).unwrap();
+			assert_eq!(winners.iter().map(|(w, _)| *w).collect::<Vec<_>>(), vec![40, 30]);
+
+			let reversed = ElectionResult {
+				winners: winners.iter().rev().cloned().collect(),
+				assignments: assignments.clone(),
+			};
+			let result = ElectionResult { winners, assignments };
+
+			let (solution, _) = MultiPhase::prepare_election_result(result).unwrap();
+			let (reversed_solution, _) = MultiPhase::prepare_election_result(reversed).unwrap();
+			assert_eq!(solution, reversed_solution);
+
+			// no spurious `InvalidScore`.
+			assert_ok!(MultiPhase::feasibility_check(reversed_solution, ElectionCompute::Unsigned));
+		})
+	}
+
+	#[test]
+	fn unsigned_per_dispatch_checks_rejects_duplicate() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let (solution, witness) = MultiPhase::mine_solution(2).unwrap();
+			assert!(MultiPhase::queued_solution_hash().is_none());
+			assert_ok!(MultiPhase::submit_unsigned(Origin::none(), solution.clone(), witness));
+			assert!(MultiPhase::queued_solution_hash().is_some());
+
+			// the exact same solution is rejected before even looking at the score.
+			assert_noop!(
+				MultiPhase::unsigned_pre_dispatch_checks(&solution),
+				Error::<Runtime>::PreDispatchDuplicateSubmission,
+			);
+
+			// and the hash is gone with the queued solution.
+			let _ = MultiPhase::elect().unwrap();
+			assert!(MultiPhase::queued_solution_hash().is_none());
+		})
+	}
+
 	#[test]
 	fn ocw_check_prevent_duplicate() {
 		let (mut ext, _) = ExtBuilder::default().build_offchainify(0);
@@ -810,27 +1326,103 @@ mod tests {
 			assert!(MultiPhase::current_phase().is_unsigned());
 
 			// first execution -- okay.
-			assert!(MultiPhase::try_acquire_offchain_lock(25).is_ok());
+			assert_eq!(
+				MultiPhase::try_acquire_offchain_lock(25),
+				OffchainExecutionDecision::Proceed,
+			);
 
 			// next block: rejected.
-			assert!(MultiPhase::try_acquire_offchain_lock(26).is_err());
+			assert_eq!(
+				MultiPhase::try_acquire_offchain_lock(26),
+				OffchainExecutionDecision::SkipRecent,
+			);
 
 			// allowed after `OFFCHAIN_REPEAT`
-			assert!(MultiPhase::try_acquire_offchain_lock((26 + OFFCHAIN_REPEAT).into()).is_ok());
+			assert_eq!(
+				MultiPhase::try_acquire_offchain_lock((26 + OFFCHAIN_REPEAT).into()),
+				OffchainExecutionDecision::Proceed,
+			);
 
 			// a fork like situation: re-execute last 3.
-			assert!(
-				MultiPhase::try_acquire_offchain_lock((26 + OFFCHAIN_REPEAT - 3).into()).is_err()
+			assert_eq!(
+				MultiPhase::try_acquire_offchain_lock((26 + OFFCHAIN_REPEAT - 3).into()),
+				OffchainExecutionDecision::SkipFork,
+			);
+			assert_eq!(
+				MultiPhase::try_acquire_offchain_lock((26 + OFFCHAIN_REPEAT - 2).into()),
+				OffchainExecutionDecision::SkipFork,
 			);
-			assert!(
-				MultiPhase::try_acquire_offchain_lock((26 + OFFCHAIN_REPEAT - 2).into()).is_err()
+			assert_eq!(
+				MultiPhase::try_acquire_offchain_lock((26 + OFFCHAIN_REPEAT - 1).into()),
+				OffchainExecutionDecision::SkipFork,
 			);
-			assert!(
-				MultiPhase::try_acquire_offchain_lock((26 + OFFCHAIN_REPEAT - 1).into()).is_err()
+		})
+	}
+
+	#[test]
+	fn ocw_reuses_cached_solution_of_the_same_round() {
+		let (mut ext, pool) = ExtBuilder::default().desired_targets(3).build_offchainify(0);
+		ext.execute_with(|| {
+			let (targets, voters) = deterministic_miner_fixture();
+			<Targets>::set(targets);
+			<Voters>::set(voters);
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			assert_ok!(MultiPhase::mine_check_and_submit());
+			let (cached, _, _) = StorageValueRef::persistent(&OFFCHAIN_CACHED_SOLUTION)
+				.get::<(RawSolution<TestCompact>, SolutionOrSnapshotSize, H256)>()
+				.unwrap()
+				.unwrap();
+			assert_eq!(cached.round, 1);
+
+			// mining again would yield a different solution, yet the cached one is re-used.
+			<MinerSolver>::set(SolverStrategy::PhragMMS);
+			assert_ok!(MultiPhase::mine_check_and_submit());
+			assert_eq!(pool.read().transactions.len(), 2);
+			assert_eq!(pool.read().transactions[0], pool.read().transactions[1]);
+
+			// once a better solution is queued, the cache is not good anymore and we re-mine.
+			let ready = ReadySolution { score: [1_000_000, 0, 0], ..Default::default() };
+			<QueuedSolution<Runtime>>::put(ready);
+			assert_eq!(
+				MultiPhase::mine_check_and_submit().unwrap_err(),
+				MinerError::PreDispatchChecksFailed,
 			);
 		})
 	}
 
+	#[test]
+	fn ocw_re_mines_if_snapshot_changes_within_round() {
+		let (mut ext, pool) = ExtBuilder::default().build_offchainify(0);
+		ext.execute_with(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			assert_ok!(MultiPhase::mine_check_and_submit());
+			let (cached, _, hash) = StorageValueRef::persistent(&OFFCHAIN_CACHED_SOLUTION)
+				.get::<(RawSolution<TestCompact>, SolutionOrSnapshotSize, H256)>()
+				.unwrap()
+				.unwrap();
+
+			// a re-org replaces the snapshot, without changing the round.
+			let mut snapshot = MultiPhase::snapshot().unwrap();
+			snapshot.voters.iter_mut().for_each(|(_, stake, _)| *stake *= 2);
+			MultiPhase::put_snapshot(snapshot);
+			assert_eq!(MultiPhase::round(), cached.round);
+
+			assert_ok!(MultiPhase::mine_check_and_submit());
+			let (re_mined, _, new_hash) = StorageValueRef::persistent(&OFFCHAIN_CACHED_SOLUTION)
+				.get::<(RawSolution<TestCompact>, SolutionOrSnapshotSize, H256)>()
+				.unwrap()
+				.unwrap();
+			assert_ne!(hash, new_hash);
+			assert_ne!(cached.score, re_mined.score);
+			assert_eq!(pool.read().transactions.len(), 2);
+			assert_ne!(pool.read().transactions[0], pool.read().transactions[1]);
+		})
+	}
+
 	#[test]
 	fn ocw_only_runs_when_signed_open_now() {
 		let (mut ext, pool) = ExtBuilder::default().build_offchainify(0);
@@ -856,6 +1448,137 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn ocw_retries_failed_submission() {
+		let (mut ext, pool) = ExtBuilder::default().pool_failures(1).build_offchainify(0);
+		ext.execute_with(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			let before = sp_io::offchain::timestamp();
+			assert_ok!(MultiPhase::mine_check_and_submit());
+			assert_eq!(pool.read().transactions.len(), 1);
+			assert_eq!(<PoolFailures>::get(), 0);
+
+			// we backed off once.
+			let waited = sp_io::offchain::timestamp().diff(&before).millis();
+			assert_eq!(waited, OFFCHAIN_SUBMIT_BACKOFF);
+		})
+	}
+
+	#[test]
+	fn ocw_miner_totals_are_counted() {
+		let (mut ext, pool) = ExtBuilder::default()
+			.pool_failures(OFFCHAIN_SUBMIT_ATTEMPTS)
+			.build_offchainify(0);
+		ext.execute_with(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert_eq!(MultiPhase::offchain_miner_totals(), (0, 0));
+
+			// all attempts fail.
+			assert!(MultiPhase::mine_check_and_submit().is_err());
+			assert_eq!(MultiPhase::offchain_miner_totals(), (0, 1));
+
+			// the pool works again.
+			assert_ok!(MultiPhase::mine_check_and_submit());
+			assert_eq!(pool.read().transactions.len(), 1);
+			assert_eq!(MultiPhase::offchain_miner_totals(), (1, 1));
+		})
+	}
+
+	#[test]
+	fn ocw_does_not_resubmit_queued_solution() {
+		let (mut ext, pool) = ExtBuilder::default().build_offchainify(0);
+		ext.execute_with(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			assert_ok!(MultiPhase::mine_check_and_submit());
+			assert_eq!(pool.read().transactions.len(), 1);
+			assert_eq!(MultiPhase::offchain_miner_totals(), (1, 0));
+
+			// the transaction is included.
+			let encoded = pool.read().transactions[0].clone();
+			let extrinsic: Extrinsic = Decode::decode(&mut &*encoded).unwrap();
+			assert_ok!(extrinsic.call.dispatch(Origin::none()));
+			assert!(MultiPhase::queued_solution().is_some());
+
+			// nothing is submitted, and this is not a failure.
+			assert_ok!(MultiPhase::mine_check_and_submit());
+			assert_eq!(pool.read().transactions.len(), 1);
+			assert_eq!(MultiPhase::offchain_miner_totals(), (1, 0));
+		})
+	}
+
+	#[test]
+	fn ocw_gives_up_after_all_attempts_fail() {
+		let (mut ext, pool) = ExtBuilder::default()
+			.pool_failures(OFFCHAIN_SUBMIT_ATTEMPTS)
+			.build_offchainify(0);
+		ext.execute_with(|| {
+			roll_to(25);
+			assert!(MultiPhase::current_phase().is_unsigned());
+
+			assert_eq!(
+				MultiPhase::mine_check_and_submit().unwrap_err(),
+				MinerError::PoolSubmissionFailed,
+			);
+			assert!(pool.read().transactions.is_empty());
+			assert_eq!(<PoolFailures>::get(), 0);
+		})
+	}
+
+	#[test]
+	fn ocw_submits_despite_stale_transaction_of_previous_round() {
+		let (mut ext, pool) = ExtBuilder::default().build_offchainify(0);
+		ext.execute_with(|| {
+			let call_at = |index: usize| {
+				let encoded = pool.read().transactions[index].clone();
+				let extrinsic: Extrinsic = Decode::decode(&mut &*encoded).unwrap();
+				match extrinsic.call {
+					OuterCall::MultiPhase(call) => call,
+					_ => panic!("unexpected call"),
+				}
+			};
+
+			// round 1: the solution is submitted, but never included.
+			roll_to_with_ocw(25);
+			assert_eq!(pool.read().transactions.len(), 1);
+			roll_to(30);
+			MultiPhase::elect().unwrap();
+
+			// round 2: the stale transaction is still in the pool, yet a new one is submitted.
+			roll_to_with_ocw(55);
+			assert_eq!(MultiPhase::round(), 2);
+			assert_eq!(pool.read().transactions.len(), 2);
+
+			let (stale, fresh) = (call_at(0), call_at(1));
+			let round_of = |call: &Call<Runtime>| match call {
+				Call::submit_unsigned(solution, _) => solution.round,
+				_ => panic!("unexpected call"),
+			};
+			assert_eq!((round_of(&stale), round_of(&fresh)), (1, 2));
+
+			// the stale one is invalid now, and will be dropped by the pool, ..
+			assert!(matches!(
+				<MultiPhase as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &stale)
+					.unwrap_err(),
+				TransactionValidityError::Invalid(InvalidTransaction::Custom(9))
+			));
+			assert!(<MultiPhase as ValidateUnsigned>::pre_dispatch(&stale).is_err());
+
+			// .. while the new one is valid, and can be included.
+			assert!(<MultiPhase as ValidateUnsigned>::validate_unsigned(
+				TransactionSource::Local,
+				&fresh
+			)
+			.is_ok());
+			assert_ok!(OuterCall::MultiPhase(fresh).dispatch(Origin::none()));
+			assert_eq!(MultiPhase::queued_solution().unwrap().compute, ElectionCompute::Unsigned);
+		})
+	}
+
 	#[test]
 	fn ocw_can_submit_to_pool() {
 		let (mut ext, pool) = ExtBuilder::default().build_offchainify(0);
@@ -81,11 +81,12 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			// Standard Error: 78_000
 			.saturating_add((229_000 as Weight).saturating_mul(t as Weight))
 			// Standard Error: 23_000
-			.saturating_add((13_661_000 as Weight).saturating_mul(a as Weight))
+			.saturating_add((15_061_000 as Weight).saturating_mul(a as Weight))
 			// Standard Error: 117_000
 			.saturating_add((4_499_000 as Weight).saturating_mul(d as Weight))
-			.saturating_add(T::DbWeight::get().reads(6 as Weight))
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(a as Weight)))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
 	fn feasibility_check(v: u32, t: u32, a: u32, d: u32, ) -> Weight {
 		(0 as Weight)
@@ -97,7 +98,8 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add((10_294_000 as Weight).saturating_mul(a as Weight))
 			// Standard Error: 64_000
 			.saturating_add((4_428_000 as Weight).saturating_mul(d as Weight))
-			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(a as Weight)))
 	}
 }
 
@@ -129,11 +131,12 @@ impl WeightInfo for () {
 			// Standard Error: 78_000
 			.saturating_add((229_000 as Weight).saturating_mul(t as Weight))
 			// Standard Error: 23_000
-			.saturating_add((13_661_000 as Weight).saturating_mul(a as Weight))
+			.saturating_add((15_061_000 as Weight).saturating_mul(a as Weight))
 			// Standard Error: 117_000
 			.saturating_add((4_499_000 as Weight).saturating_mul(d as Weight))
-			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(a as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 	}
 	fn feasibility_check(v: u32, t: u32, a: u32, d: u32, ) -> Weight {
 		(0 as Weight)
@@ -145,6 +148,7 @@ impl WeightInfo for () {
 			.saturating_add((10_294_000 as Weight).saturating_mul(a as Weight))
 			// Standard Error: 64_000
 			.saturating_add((4_428_000 as Weight).saturating_mul(d as Weight))
-			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(a as Weight)))
 	}
 }
@@ -159,7 +159,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub mod onchain;
-use sp_std::{prelude::*, fmt::Debug};
+use sp_std::{collections::btree_set::BTreeSet, fmt::Debug, prelude::*};
 
 /// Re-export some type as they are used in the interface.
 pub use sp_arithmetic::PerThing;
@@ -186,6 +186,12 @@ pub trait ElectionDataProvider<AccountId, BlockNumber> {
 	/// This is only useful for stateful election providers.
 	fn next_election_prediction(now: BlockNumber) -> BlockNumber;
 
+	/// Called once an [`ElectionProvider`] has successfully finished an election that used the
+	/// data of this provider, such that any data cached for the election can be released.
+	///
+	/// The default implementation does nothing.
+	fn on_election_completed() {}
+
 	/// Utility function only to be used in benchmarking scenarios, to be implemented optionally,
 	/// else a noop.
 	#[cfg(any(feature = "runtime-benchmarks", test))]
@@ -228,6 +234,45 @@ pub trait ElectionProvider<AccountId, BlockNumber> {
 	///
 	/// The result is returned in a target major format, namely as vector of  supports.
 	fn elect() -> Result<Supports<AccountId>, Self::Error>;
+
+	/// Elect a new set of winners, given that the election is computed over at most `max_voters`
+	/// voters and `max_targets` targets.
+	///
+	/// Implementations that know how to trim their input should do so, and those that don't should
+	/// return an error if any of the bounds is exceeded.
+	///
+	/// The default implementation runs [`ElectionProvider::elect`], and returns [`BoundsExceeded`]
+	/// if more than `max_targets` winners are elected, or if they are backed by more than
+	/// `max_voters` distinct voters. Note that any side effect of `elect` has happened by then.
+	fn elect_with_bounds(
+		max_voters: usize,
+		max_targets: usize,
+	) -> Result<Supports<AccountId>, Self::Error>
+	where
+		AccountId: Ord,
+		Self::Error: From<BoundsExceeded>,
+	{
+		let supports = Self::elect()?;
+		let voters = supports
+			.iter()
+			.flat_map(|(_, support)| support.voters.iter().map(|(voter, _)| voter))
+			.collect::<BTreeSet<_>>()
+			.len();
+		if supports.len() > max_targets || voters > max_voters {
+			return Err(BoundsExceeded.into());
+		}
+		Ok(supports)
+	}
+}
+
+/// The result of an election exceeds the bounds given to [`ElectionProvider::elect_with_bounds`].
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct BoundsExceeded;
+
+impl From<BoundsExceeded> for &'static str {
+	fn from(_: BoundsExceeded) -> Self {
+		"election bounds exceeded"
+	}
 }
 
 #[cfg(feature = "std")]
@@ -239,3 +284,81 @@ impl<AccountId, BlockNumber> ElectionProvider<AccountId, BlockNumber> for () {
 		Err("<() as ElectionProvider> cannot do anything.")
 	}
 }
+
+/// An election provider that never elects anyone: [`ElectionProvider::elect`] always returns an
+/// empty set of supports, without reading any data from the data provider.
+///
+/// Useful for chains that do not use elections, and in tests. `X` is expected to be the
+/// `(AccountId, BlockNumber, DataProvider)` tuple.
+pub struct NoElection<X>(sp_std::marker::PhantomData<X>);
+
+impl<AccountId, BlockNumber, DataProvider> ElectionProvider<AccountId, BlockNumber>
+	for NoElection<(AccountId, BlockNumber, DataProvider)>
+where
+	DataProvider: ElectionDataProvider<AccountId, BlockNumber>,
+{
+	type Error = &'static str;
+	type DataProvider = DataProvider;
+
+	fn elect() -> Result<Supports<AccountId>, Self::Error> {
+		Ok(Default::default())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_npos_elections::Support;
+
+	type AccountId = u64;
+	type BlockNumber = u32;
+
+	/// A data provider that must never be read from.
+	struct Untouchable;
+	impl ElectionDataProvider<AccountId, BlockNumber> for Untouchable {
+		fn targets() -> Vec<AccountId> {
+			unreachable!("targets must not be read")
+		}
+		fn voters() -> Vec<(AccountId, VoteWeight, Vec<AccountId>)> {
+			unreachable!("voters must not be read")
+		}
+		fn desired_targets() -> u32 {
+			unreachable!("desired targets must not be read")
+		}
+		fn next_election_prediction(now: BlockNumber) -> BlockNumber {
+			now
+		}
+	}
+
+	/// An election provider with a fixed result.
+	struct Fixed;
+	impl ElectionProvider<AccountId, BlockNumber> for Fixed {
+		type Error = &'static str;
+		type DataProvider = Untouchable;
+
+		fn elect() -> Result<Supports<AccountId>, Self::Error> {
+			Ok(vec![
+				(10, Support { total: 15, voters: vec![(1, 5), (2, 10)] }),
+				(20, Support { total: 25, voters: vec![(2, 5), (3, 20)] }),
+			])
+		}
+	}
+
+	#[test]
+	fn default_elect_with_bounds_checks_the_result() {
+		// 2 winners, backed by 3 distinct voters.
+		assert_eq!(Fixed::elect_with_bounds(3, 2), Fixed::elect());
+		assert_eq!(Fixed::elect_with_bounds(usize::max_value(), usize::max_value()), Fixed::elect());
+
+		assert_eq!(Fixed::elect_with_bounds(2, 2), Err("election bounds exceeded"));
+		assert_eq!(Fixed::elect_with_bounds(3, 1), Err("election bounds exceeded"));
+		assert_eq!(Fixed::elect_with_bounds(0, 0), Err("election bounds exceeded"));
+	}
+
+	#[test]
+	fn no_election_elects_nobody() {
+		type Provider = NoElection<(AccountId, BlockNumber, Untouchable)>;
+		assert_eq!(Provider::elect().unwrap(), vec![]);
+		assert_eq!(Provider::elect_with_bounds(0, 0).unwrap(), vec![]);
+	}
+}
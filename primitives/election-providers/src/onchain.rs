@@ -17,7 +17,7 @@
 
 //! An implementation of [`ElectionProvider`] that does an on-chain sequential phragmen.
 
-use crate::{ElectionDataProvider, ElectionProvider};
+use crate::{BoundsExceeded, ElectionDataProvider, ElectionProvider};
 use sp_npos_elections::*;
 use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, prelude::*};
 
@@ -26,6 +26,9 @@ use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, prelude::*};
 pub enum Error {
 	/// An internal error in the NPoS elections crate.
 	NposElections(sp_npos_elections::Error),
+	/// The election data exceeds the bounds given to
+	/// [`ElectionProvider::elect_with_bounds`].
+	BoundsExceeded,
 }
 
 impl From<sp_npos_elections::Error> for Error {
@@ -34,13 +37,30 @@ impl From<sp_npos_elections::Error> for Error {
 	}
 }
 
+impl From<BoundsExceeded> for Error {
+	fn from(_: BoundsExceeded) -> Self {
+		Error::BoundsExceeded
+	}
+}
+
+impl sp_std::fmt::Display for Error {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		match self {
+			Error::NposElections(e) => write!(f, "npos elections error: {:?}", e),
+			Error::BoundsExceeded => write!(f, "election bounds exceeded"),
+		}
+	}
+}
+
 /// A simple on-chain implementation of the election provider trait.
 ///
 /// This will accept voting data on the fly and produce the results immediately.
 ///
 /// ### Warning
 ///
-/// This can be very expensive to run frequently on-chain. Use with care.
+/// This can be very expensive to run frequently on-chain. Use with care. The size of the election
+/// can be bounded with [`ElectionProvider::elect_with_bounds`]. No trimming is ever done, instead
+/// [`Error::BoundsExceeded`] is returned if the data of the election is larger than the bounds.
 pub struct OnChainSequentialPhragmen<T: Config>(PhantomData<T>);
 
 /// Configuration trait of [`OnChainSequentialPhragmen`].
@@ -64,7 +84,28 @@ impl<T: Config> ElectionProvider<T::AccountId, T::BlockNumber> for OnChainSequen
 	fn elect() -> Result<Supports<T::AccountId>, Self::Error> {
 		let voters = Self::DataProvider::voters();
 		let targets = Self::DataProvider::targets();
-		let desired_targets = Self::DataProvider::desired_targets() as usize;
+		Self::elect_with(voters, targets)
+	}
+
+	fn elect_with_bounds(
+		max_voters: usize,
+		max_targets: usize,
+	) -> Result<Supports<T::AccountId>, Self::Error> {
+		let voters = Self::DataProvider::voters();
+		let targets = Self::DataProvider::targets();
+		if voters.len() > max_voters || targets.len() > max_targets {
+			return Err(Error::BoundsExceeded);
+		}
+		Self::elect_with(voters, targets)
+	}
+}
+
+impl<T: Config> OnChainSequentialPhragmen<T> {
+	fn elect_with(
+		voters: Vec<(T::AccountId, VoteWeight, Vec<T::AccountId>)>,
+		targets: Vec<T::AccountId>,
+	) -> Result<Supports<T::AccountId>, Error> {
+		let desired_targets = T::DataProvider::desired_targets() as usize;
 
 		let mut stake_map: BTreeMap<T::AccountId, VoteWeight> = BTreeMap::new();
 
@@ -134,6 +175,16 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn error_display_works() {
+		let npos = || sp_npos_elections::Error::CompactInvalidIndex;
+		assert_eq!(
+			Error::NposElections(npos()).to_string(),
+			format!("npos elections error: {:?}", npos()),
+		);
+		assert_eq!(Error::BoundsExceeded.to_string(), "election bounds exceeded");
+	}
+
 	#[test]
 	fn onchain_seq_phragmen_works() {
 		assert_eq!(
@@ -156,4 +207,17 @@ mod tests {
 			]
 		);
 	}
+
+	#[test]
+	fn onchain_seq_phragmen_within_bounds_works() {
+		assert_eq!(OnChainPhragmen::elect_with_bounds(3, 3), OnChainPhragmen::elect());
+		assert!(OnChainPhragmen::elect_with_bounds(usize::max_value(), 3).is_ok());
+	}
+
+	#[test]
+	fn onchain_seq_phragmen_over_bounds_fails() {
+		assert_eq!(OnChainPhragmen::elect_with_bounds(2, 3), Err(Error::BoundsExceeded));
+		assert_eq!(OnChainPhragmen::elect_with_bounds(3, 2), Err(Error::BoundsExceeded));
+		assert_eq!(OnChainPhragmen::elect_with_bounds(0, 0), Err(Error::BoundsExceeded));
+	}
 }
@@ -233,7 +233,7 @@ pub trait PerThing128: PerThing + Mul<ExtendedBalance, Output = ExtendedBalance>
 impl<T: PerThing + Mul<ExtendedBalance, Output = ExtendedBalance>> PerThing128 for T {}
 
 /// The errors that might occur in the this crate and compact.
-#[derive(Eq, PartialEq, RuntimeDebug)]
+#[derive(Eq, PartialEq, RuntimeDebug, Encode, Decode)]
 pub enum Error {
 	/// While going from compact to staked, the stake of all the edges has gone above the total and
 	/// the last stake cannot be assigned.
@@ -243,7 +243,7 @@ pub enum Error {
 	/// One of the index functions returned none.
 	CompactInvalidIndex,
 	/// An error occurred in some arithmetic operation.
-	ArithmeticError(&'static str),
+	ArithmeticError(#[codec(skip)] &'static str),
 	/// The data provided to create support map was invalid.
 	InvalidSupportEdge,
 }
@@ -259,6 +259,43 @@ pub type ExtendedBalance = u128;
 /// [`EvaluateSupport::evaluate`].
 pub type ElectionScore = [ExtendedBalance; 3];
 
+/// Wrapper to display an [`ElectionScore`] in a human readable way, e.g. in logs.
+///
+/// Each element of the score is shown with a metric suffix and two decimals, e.g. `1.50k` for
+/// `1500`.
+pub struct DisplayScore<'a>(pub &'a ElectionScore);
+
+impl sp_std::fmt::Display for DisplayScore<'_> {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter<'_>) -> sp_std::fmt::Result {
+		let [minimal, sum, sum_squared] = *self.0;
+		f.write_str("minimal stake ")?;
+		fmt_metric(f, minimal)?;
+		f.write_str(", total stake ")?;
+		fmt_metric(f, sum)?;
+		f.write_str(", sum of squared stakes ")?;
+		fmt_metric(f, sum_squared)
+	}
+}
+
+/// Write `value` with the largest metric suffix that keeps its integer part non-zero.
+fn fmt_metric(f: &mut sp_std::fmt::Formatter<'_>, value: ExtendedBalance) -> sp_std::fmt::Result {
+	const SUFFIXES: [&str; 9] = ["", "k", "M", "G", "T", "P", "E", "Z", "Y"];
+	let mut unit: ExtendedBalance = 1;
+	let mut suffix = 0;
+	while suffix + 1 < SUFFIXES.len() && value / unit >= 1000 {
+		unit *= 1000;
+		suffix += 1;
+	}
+
+	if suffix == 0 {
+		write!(f, "{}", value)
+	} else {
+		// `value % unit < unit <= 10^24`, this cannot overflow.
+		let fraction = (value % unit) * 100 / unit;
+		write!(f, "{}.{:02}{}", value / unit, fraction, SUFFIXES[suffix])
+	}
+}
+
 /// A winner, with their respective approval stake.
 pub type WithApprovalOf<A> = (A, ExtendedBalance);
 
@@ -663,6 +700,42 @@ pub fn to_supports<A: IdentifierT>(
 	to_support_map(winners, assignments).map(FlattenSupportMap::flatten)
 }
 
+/// The difference between two [`Supports`], as computed by [`diff_supports`].
+#[derive(Default, RuntimeDebug, Clone, Eq, PartialEq)]
+pub struct SupportsDiff<A> {
+	/// Winners of the new supports that are not winners of the old one.
+	pub added: Vec<A>,
+	/// Winners of the old supports that are not winners of the new one.
+	pub removed: Vec<A>,
+	/// Winners of both supports, with their total backing in the old and the new one respectively.
+	pub retained: Vec<(A, ExtendedBalance, ExtendedBalance)>,
+}
+
+/// Compute the difference between the `old` and `new` supports, e.g. of two consecutive elections.
+///
+/// `added` and `retained` are in the order of `new`, `removed` in the order of `old`.
+pub fn diff_supports<A: Clone + Ord>(old: &Supports<A>, new: &Supports<A>) -> SupportsDiff<A> {
+	let totals = |supports: &Supports<A>| {
+		supports.iter().map(|(who, support)| (who.clone(), support.total)).collect::<BTreeMap<_, _>>()
+	};
+	let old_totals = totals(old);
+	let new_totals = totals(new);
+
+	let mut diff = SupportsDiff { added: Vec::new(), removed: Vec::new(), retained: Vec::new() };
+	for (who, support) in new.iter() {
+		match old_totals.get(who) {
+			Some(old_total) => diff.retained.push((who.clone(), *old_total, support.total)),
+			None => diff.added.push(who.clone()),
+		}
+	}
+	diff.removed = old
+		.iter()
+		.filter(|(who, _)| !new_totals.contains_key(who))
+		.map(|(who, _)| who.clone())
+		.collect();
+	diff
+}
+
 /// Extension trait for evaluating a support map or vector.
 pub trait EvaluateSupport<K> {
 	/// Evaluate a support map. The returned tuple contains:
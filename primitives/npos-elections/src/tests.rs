@@ -20,7 +20,7 @@
 use crate::{
 	balancing, helpers::*, is_score_better, mock::*, seq_phragmen, seq_phragmen_core, setup_inputs,
 	to_support_map, to_supports, Assignment, ElectionResult, ExtendedBalance, StakedAssignment,
-	Support, Voter, EvaluateSupport,
+	Support, Voter, EvaluateSupport, diff_supports, SupportsDiff, DisplayScore,
 };
 use sp_arithmetic::{PerU16, Perbill, Percent, Permill};
 use substrate_test_utils::assert_eq_uvec;
@@ -866,6 +866,37 @@ fn support_map_and_vec_can_be_evaluated() {
 	assert_eq!(support_map.evaluate(), support_vec.evaluate());
 }
 
+#[test]
+fn diff_supports_works() {
+	let support = |total: ExtendedBalance| Support { total, voters: vec![] };
+	let old = vec![(10, support(100)), (20, support(50)), (30, support(70))];
+	let new = vec![(40, support(90)), (30, support(70)), (10, support(120))];
+
+	assert_eq!(
+		diff_supports(&old, &new),
+		SupportsDiff {
+			added: vec![40],
+			removed: vec![20],
+			retained: vec![(30, 70, 70), (10, 100, 120)],
+		},
+	);
+
+	// nothing changes.
+	assert_eq!(
+		diff_supports(&old, &old),
+		SupportsDiff {
+			added: vec![],
+			removed: vec![],
+			retained: vec![(10, 100, 100), (20, 50, 50), (30, 70, 70)],
+		},
+	);
+
+	// from and to empty.
+	let empty = vec![];
+	assert_eq!(diff_supports(&empty, &old).added, vec![10, 20, 30]);
+	assert_eq!(diff_supports(&old, &empty).removed, vec![10, 20, 30]);
+}
+
 mod assignment_convert_normalize {
 	use super::*;
 	#[test]
@@ -1552,3 +1583,20 @@ mod solution_type {
 		);
 	}
 }
+
+#[test]
+fn display_score_works() {
+	assert_eq!(
+		DisplayScore(&[0, 999, 1000]).to_string(),
+		"minimal stake 0, total stake 999, sum of squared stakes 1.00k",
+	);
+	assert_eq!(
+		DisplayScore(&[1_500, 45_600_000, 7_891_000_000]).to_string(),
+		"minimal stake 1.50k, total stake 45.60M, sum of squared stakes 7.89G",
+	);
+	// the largest suffix is used for anything beyond.
+	assert_eq!(
+		DisplayScore(&[10u128.pow(24), 10u128.pow(27), u128::max_value()]).to_string(),
+		"minimal stake 1.00Y, total stake 1000.00Y, sum of squared stakes 340282366920938.46Y",
+	);
+}